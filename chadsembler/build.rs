@@ -0,0 +1,98 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// One row of the declarative instruction spec in `instructions.in`
+struct InstructionSpec {
+
+    mnemonic: String,
+    opcode: usize,
+    operands: usize,
+    register_only_operand: bool,
+    immediate_disallowed: bool
+}
+
+/// Parse `instructions.in` into its instruction specs, skipping blank lines and `#` comments
+fn parse_spec(source: &str) -> Vec<InstructionSpec> {
+
+    source.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+
+            let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+
+            InstructionSpec {
+                mnemonic: fields[0].to_owned(),
+                opcode: fields[1].parse().expect("instructions.in: opcode must be an integer"),
+                operands: fields[2].parse().expect("instructions.in: operand count must be an integer"),
+                register_only_operand: fields[3].parse().expect("instructions.in: register_only_operand must be true/false"),
+                immediate_disallowed: fields[4].parse().expect("instructions.in: immediate_disallowed must be true/false")
+            }
+        })
+        .collect()
+}
+
+/// Generate the `Instruction` consts and the mnemonic -> Instruction phf map for `csm::architecture::instructions`
+fn generate_instruction_table(specs: &[InstructionSpec]) -> String {
+
+    let mut consts = String::new();
+    let mut map_entries = String::new();
+
+    for spec in specs {
+
+        consts += &format!(
+            "    pub const {mnemonic}: Instruction = Instruction {{ mnemonic: \"{mnemonic}\", opcode: {opcode}, operands: {operands}, register_only_operand: {register_only}, immediate_disallowed: {immediate_disallowed} }};\n",
+            mnemonic = spec.mnemonic, opcode = spec.opcode, operands = spec.operands,
+            register_only = spec.register_only_operand, immediate_disallowed = spec.immediate_disallowed
+        );
+
+        map_entries += &format!("        \"{mnemonic}\" => &{mnemonic},\n", mnemonic = spec.mnemonic);
+    }
+
+    format!(
+"{consts}
+    pub const NUMBER_INSTRUCTIONS: usize = {count};
+
+    pub const INSTRUCTION_SET: phf::Map<&str, &Instruction> = phf::phf_map! {{
+{map_entries}    }};
+", count = specs.len())
+}
+
+/// Generate the opcode-ordered dispatch table consumed by `MachineOperations::execute`
+/// Opcode order is taken straight from the spec, so the dispatch table can never drift out of sync with it
+/// Emitted as just the array literal, spliced into the body of a hand-written `fn operations()` inside
+/// `impl<'a> MachineOperations<'a>`: the handlers are inherent methods of `MachineOperations<'a>`, so the array
+/// of their fn-pointers can only be built somewhere that already sees that lifetime, not as a free `const` -
+/// and `include!`ing a full `fn` item into an `impl` block doesn't work, so only the array body is generated
+fn generate_operations_table(specs: &[InstructionSpec]) -> String {
+
+    let mut ordered: Vec<&InstructionSpec> = specs.iter().collect();
+    ordered.sort_by_key(|spec| spec.opcode);
+
+    let handlers = ordered.iter()
+        .map(|spec| format!("MachineOperations::{}", spec.mnemonic))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!("[{handlers}]")
+}
+
+fn main() {
+
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
+    let spec_path = Path::new(&manifest_dir).join("instructions.in");
+
+    let spec_source = fs::read_to_string(&spec_path).expect("failed to read instructions.in");
+    let specs = parse_spec(&spec_source);
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+
+    fs::write(Path::new(&out_dir).join("instructions_table.rs"), generate_instruction_table(&specs))
+        .expect("failed to write generated instructions_table.rs");
+
+    fs::write(Path::new(&out_dir).join("operations_table.rs"), generate_operations_table(&specs))
+        .expect("failed to write generated operations_table.rs");
+
+    println!("cargo:rerun-if-changed={}", spec_path.display());
+}
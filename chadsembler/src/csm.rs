@@ -0,0 +1,11 @@
+pub mod architecture;
+pub mod binarystring;
+pub mod capabilities;
+pub mod defaults;
+pub mod disassembler;
+pub mod errors;
+pub mod faults;
+pub mod machineoperations;
+pub mod sourcemap;
+pub mod structs;
+pub mod tokens;
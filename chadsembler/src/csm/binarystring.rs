@@ -116,6 +116,24 @@ pub fn read_signed_int(binary_string: &str) -> Result<isize, std::num::ParseIntE
     Ok(sign * value)
 }
 
+/// Determine the carry-out and signed-overflow that adding `left` and `right` would produce
+/// when both are represented using `bits` bits, without needing to compute or store the sum itself
+/// Carry-out wraps the operands the same way `unsigned_int` would and checks whether their sum spills past the width
+/// Signed-overflow checks whether the true sum still fits the signed range `signed_int` can represent
+pub fn add_carry_overflow(left: isize, right: isize, bits: isize) -> (bool, bool) {
+
+    let bits = std::cmp::max(bits, 2);
+    let width = 2isize.pow(bits as u32);
+    let max_magnitude = width / 2 - 1;
+
+    let result = left + right;
+
+    let carry = overflow(left, bits as u32) + overflow(right, bits as u32) >= width;
+    let overflowed = result < -max_magnitude || result > max_magnitude;
+
+    (carry, overflowed)
+}
+
 // ====================== Bitwise Shift Instructions
 
 /// Perform a logical left shift, n times
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use crate::csm::faults::Fault;
+
+/// Syscall numbers `SYSCALL` dispatches on, held in ACC
+pub mod numbers {
+
+    pub const OPEN: isize = 0;
+    pub const READ: isize = 1;
+    pub const WRITE: isize = 2;
+    pub const CLOSE: isize = 3;
+    pub const EXIT: isize = 4;
+}
+
+/// Bits an `open` syscall's flags argument can set, combined with bitwise OR
+pub mod openflags {
+
+    pub const READ: isize = 1;
+    pub const WRITE: isize = 2;
+    pub const APPEND: isize = 4;
+    pub const CREATE: isize = 8;
+    pub const EXCLUSIVE: isize = 16;
+    pub const TRUNCATE: isize = 32;
+}
+
+/// The host-granted permission to open a fixed set of files, and the table of currently open handles
+/// `allowed_paths` is the entire grant: a path not in it can never be opened, no matter what flags are passed
+/// Handles are assigned sequentially and are never reused, even once `close`d, mirroring how `Memory` hands out
+/// addresses rather than letting a program pick its own
+pub struct Capabilities {
+
+    allowed_paths: Vec<PathBuf>,
+    handles: HashMap<isize, File>,
+    next_handle: isize
+}
+
+impl Capabilities {
+
+    /// Constructor for a 'Capabilities' object
+    /// Pass an empty `allowed_paths` to deny all filesystem access, the safe default for an untrusted program
+    pub fn new(allowed_paths: Vec<PathBuf>) -> Self {
+
+        Capabilities {
+            allowed_paths,
+            handles: HashMap::new(),
+            next_handle: 1
+        }
+    }
+
+    /// Open `path` if it was granted, honouring `flags` as a bitwise OR of `openflags`, returning a handle
+    /// for `read`/`write`/`close` to index into
+    pub fn open(&mut self, path: &str, flags: isize) -> Result<isize, Fault> {
+
+        let path = PathBuf::from(path);
+
+        if !self.allowed_paths.contains(&path) {
+
+            return Err(Fault::CapabilityDenied);
+        }
+
+        let file = OpenOptions::new()
+            .read(flags & openflags::READ != 0)
+            .write(flags & openflags::WRITE != 0)
+            .append(flags & openflags::APPEND != 0)
+            .create(flags & openflags::CREATE != 0)
+            .create_new(flags & openflags::EXCLUSIVE != 0)
+            .truncate(flags & openflags::TRUNCATE != 0)
+            .open(path)
+            .map_err(|_| Fault::InvalidSyscall(numbers::OPEN))?;
+
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        self.handles.insert(handle, file);
+
+        Ok(handle)
+    }
+
+    /// Read up to `length` bytes from an open handle, returning the bytes actually read
+    pub fn read(&mut self, handle: isize, length: usize) -> Result<Vec<u8>, Fault> {
+
+        let file = self.handles.get_mut(&handle).ok_or(Fault::InvalidSyscall(numbers::READ))?;
+
+        let mut buffer = vec![0u8; length];
+        let read = file.read(&mut buffer).map_err(|_| Fault::InvalidSyscall(numbers::READ))?;
+        buffer.truncate(read);
+
+        Ok(buffer)
+    }
+
+    /// Write `data` to an open handle, returning the number of bytes actually written
+    pub fn write(&mut self, handle: isize, data: &[u8]) -> Result<usize, Fault> {
+
+        let file = self.handles.get_mut(&handle).ok_or(Fault::InvalidSyscall(numbers::WRITE))?;
+
+        file.write(data).map_err(|_| Fault::InvalidSyscall(numbers::WRITE))
+    }
+
+    /// Close an open handle; its number is not handed out again by a later `open`
+    pub fn close(&mut self, handle: isize) -> Result<(), Fault> {
+
+        self.handles.remove(&handle).ok_or(Fault::InvalidSyscall(numbers::CLOSE))?;
+
+        Ok(())
+    }
+}
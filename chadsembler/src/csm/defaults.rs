@@ -1,5 +1,9 @@
 pub mod sysdefaults {
 
+    use std::collections::HashMap;
+    use crate::csm::binarystring;
+    use crate::csm::architecture::registers;
+
     /// Ensure a value falls between the specified lower and upper limits (inclusive)
     /// Follows 2s complement bounds wrapping rules
     pub fn wrap_bound(lower: isize, upper: isize, value: isize) -> isize {
@@ -7,6 +11,21 @@ pub mod sysdefaults {
         lower + (value - lower) % (upper + 1 - lower)
     }
 
+    /// The number of bits an operand field needs: wide enough to address either every register or every
+    /// memory address, whichever needs more, plus a sign bit
+    /// The single source of truth for this calculation - the code generator sizes machine words with it and
+    /// the semantic analyser uses it to range-check operand values before they are encoded
+    pub fn operand_bits(config_table: &HashMap<String, usize>) -> usize {
+
+        let number_gprs = config_table[REGISTERS_CONFIG.0];
+        let number_registers = number_gprs + registers::NUMBER_SP_REGISTERS;
+        let number_memory_addresses = config_table[MEMORY_CONFIG.0];
+
+        (if number_registers > number_memory_addresses
+            { binarystring::number_bits(number_registers) }
+            else { binarystring::number_bits(number_memory_addresses) }) + 1
+    }
+
     /// Apply default casing to a string
     pub fn default_casing(uncased_string: &str) -> String {
 
@@ -17,6 +36,8 @@ pub mod sysdefaults {
     pub const MEMORY_CONFIG: (&str, usize)    = ("MEMORY",    100);
     pub const REGISTERS_CONFIG: (&str, usize) = ("REGISTERS",   3);
     pub const CLOCK_CONFIG: (&str, usize)     = ("CLOCK",       0);
+    /// A value of 0 means no limit, see `virtualmachine::run` where this is translated into `max_cycles: Option<u64>`
+    pub const CYCLES_CONFIG: (&str, usize)    = ("CYCLES",      0);
 
     pub const DIRECTIVE_PREFIX: char = '!';
     pub const COMMENT_PREFIX: char   = ';';
@@ -31,6 +52,7 @@ pub mod sysdefaults {
     pub const CSM_EXTENSION: &str = ".csm";
 
     pub const ARGUMENT_PROCESSOR_ERRORS_HEADER: &str = "Argument Processor Errors:";
+    pub const EXPANDER_ERRORS_HEADER: &str           = "Expander Errors:";
     pub const PREPROCESSOR_ERRORS_HEADER: &str       = "Preprocessor Errors:";
     pub const LEXER_ERRORS_HEADER: &str              = "Lexer Errors:";
     pub const PARSER_ERRORS_HEADER: &str             = "Parser Errors:";
@@ -0,0 +1,127 @@
+use std::ops::Range;
+
+use crate::csm::architecture::{instructions, addressingmodes, registers};
+use crate::csm::binarystring;
+use crate::csm::structs::{Instruction, Memory};
+
+/// Reverse-map an opcode back to its 'Instruction'
+/// Looks the opcode up in `instructions::INSTRUCTION_SET`, generated from the same `instructions.in` spec as the
+/// dispatch table `MachineOperations::execute` indexes, so the assembler and disassembler can never drift out of sync
+pub(crate) fn instruction_by_opcode(opcode: usize) -> Option<&'static Instruction> {
+
+    instructions::INSTRUCTION_SET.values().find(|instruction| instruction.opcode as usize == opcode).copied()
+}
+
+/// Reverse-map an addressing mode opcode back to its symbol
+pub(crate) fn addressing_mode_symbol(opcode: usize) -> &'static str {
+
+    addressingmodes::ADDRESSING_MODES.values()
+        .find(|mode| mode.opcode == opcode)
+        .map_or("?", |mode| mode.symbol)
+}
+
+/// Reverse-map a decoded negative operand value back to the register token it was encoded from
+/// Mirrors the negative-address register encoding `resolve_operand` uses (both in the code generator and the
+/// virtual machine): SP registers were encoded as `-(number_gprs + spr.offset)`, GPRs as `-n`
+fn unresolve_register(value: isize, number_gprs: usize) -> String {
+
+    let offset = (-value) as usize;
+
+    if offset > number_gprs {
+
+        let spr_offset = offset - number_gprs;
+
+        registers::SP_REGISTERS.values()
+            .find(|spr| spr.offset == spr_offset)
+            .map_or_else(|| value.to_string(), |spr| spr.register.to_owned())
+
+    } else {
+
+        format!("REG{offset}")
+    }
+}
+
+/// Render a single decoded operand as `<symbol><value>`, e.g. `@12` or `#-4`, decoding negative values
+/// back to register names (`ACC`/`PC`/`RR`/`FR`/`REGn`)
+fn format_operand(addressing_mode_opcode: usize, operand_value: isize, number_gprs: usize) -> String {
+
+    let operand_text = if addressing_mode_opcode == addressingmodes::REGISTER.opcode && operand_value < 0
+        { unresolve_register(operand_value, number_gprs) }
+        else { operand_value.to_string() };
+
+    format!("{}{}", addressing_mode_symbol(addressing_mode_opcode), operand_text)
+}
+
+/// Render a single machine word as its Chadsembler mnemonic and operands
+/// `addressing_mode_opcode` only applies to the source operand, the destination is always register-addressed
+/// `number_gprs` lets negative operand values be decoded back to register names instead of shown as raw numbers
+/// When `opcode` isn't a recognised instruction the word is treated as data and shown as `raw_value`, its
+/// plain signed interpretation
+pub fn disassemble_instruction(opcode: usize, addressing_mode_opcode: usize, source_value: isize, destination_value: isize, number_gprs: usize, raw_value: isize) -> String {
+
+    match instruction_by_opcode(opcode) {
+
+        Some(instruction) => {
+
+            let mut line = instruction.mnemonic.to_owned();
+
+            if instruction.operands > 0 {
+
+                line += " ";
+                line += &format_operand(addressing_mode_opcode, source_value, number_gprs);
+            }
+
+            if instruction.operands > 1 {
+
+                line += ", ";
+                line += &format_operand(addressingmodes::REGISTER.opcode, destination_value, number_gprs);
+            }
+
+            line
+        },
+
+        None => format!("DAT {raw_value}")
+    }
+}
+
+/// Split a machine word into its opcode, addressing mode and two raw operand fields
+pub(crate) fn split_word(machine_code: &str, bits: &(usize, usize, usize, usize)) -> (usize, usize, isize, isize) {
+
+    let (mut lower, mut upper) = (0, bits.0);
+    let opcode = binarystring::read_unsigned_int(&machine_code[lower..upper]).unwrap() as usize;
+
+    (lower, upper) = (upper, upper + bits.1);
+    let addressing_mode = binarystring::read_unsigned_int(&machine_code[lower..upper]).unwrap() as usize;
+
+    (lower, upper) = (upper, upper + bits.2);
+    let source_value = binarystring::read_signed_int(&machine_code[lower..upper]).unwrap();
+
+    (lower, upper) = (upper, upper + bits.2);
+    let destination_value = binarystring::read_signed_int(&machine_code[lower..upper]).unwrap();
+
+    (opcode, addressing_mode, source_value, destination_value)
+}
+
+/// Walk a range of memory addresses and produce a full listing, one line per address, prefixed with the address
+/// `number_gprs` is needed to tell register operands apart from direct/immediate ones
+/// Invaluable for debugging generated programs and for a future single-step debugger
+pub fn disassemble(memory: &Memory, bits: &(usize, usize, usize, usize), number_gprs: usize, addresses: Range<isize>) -> String {
+
+    let mut listing = String::new();
+
+    for address in addresses {
+
+        let machine_code = match memory.get(address) {
+
+            Ok(machine_code) => machine_code,
+            Err(_) => continue
+        };
+
+        let (opcode, addressing_mode, source_value, destination_value) = split_word(&machine_code, bits);
+        let raw_value = binarystring::read_signed_int(&machine_code).unwrap();
+
+        listing += &format!("{address}: {}\n", disassemble_instruction(opcode, addressing_mode, source_value, destination_value, number_gprs, raw_value));
+    }
+
+    listing
+}
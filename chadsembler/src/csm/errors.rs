@@ -1,27 +1,86 @@
-use std::{fmt, process};
-use crate::csm::defaults::sysdefaults;
+use std::fmt;
+use std::io::IsTerminal;
+
+/// The offending source line plus how many columns (starting at the error's own `column`) the caret underline
+/// should cover - grouped together so a method that also needs a trailing note, like `record_error_noted`,
+/// doesn't have to carry both as their own flat parameters on top of everything else
+pub struct Span {
+
+    pub source_line: String,
+    pub span: usize
+}
+
+/// A second span an error report can point at alongside its primary location, e.g. the previous token in a
+/// two-token parser error, or the opening `{` of a scope a later error reports as never closed
+struct RelatedSpan {
+
+    row: usize,
+    column: usize,
+    label: String,
+    source_line: String,
+    span: usize
+}
 
 /// An error that can be found at a specific position
+/// `source_line`/`span` are optional: when present the error renders as a caret-underlined diagnostic,
+/// pointing at the exact column range on the offending line, instead of a flat coordinate
+/// `note` is an optional trailing suggestion, e.g. pointing at the nearest in-scope symbol name
+/// `related` is an optional second span, rendered as its own framed/underlined block, for errors that only
+/// make sense in terms of two locations at once
 struct Error {
 
     row: usize,
     column: usize,
     error_type: &'static str,
-    error_message: &'static str
+    error_message: String,
+    source_line: Option<String>,
+    span: usize,
+    note: Option<String>,
+    related: Option<RelatedSpan>
 }
 
 impl fmt::Display for Error {
 
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        
-        write!(f, "{} {}:{} -> {}", self.error_type, self.row, self.column, self.error_message)
+
+        write!(f, "{} {}:{} -> {}", self.error_type, self.row, self.column, self.error_message)?;
+
+        // Caret-underlined framed reports assume a human is reading a terminal; redirected/piped output
+        // falls back to the flat "row:column -> message" line above, with nothing further written
+        if !std::io::stderr().is_terminal() {
+
+            return Ok(());
+        }
+
+        if let Some(source_line) = &self.source_line {
+
+            let indent = " ".repeat(self.column.saturating_sub(1));
+            let underline = "^".repeat(self.span.max(1));
+
+            write!(f, "\n    | {source_line}\n    | {indent}{underline}")?;
+        }
+
+        if let Some(related) = &self.related {
+
+            let indent = " ".repeat(related.column.saturating_sub(1));
+            let underline = "^".repeat(related.span.max(1));
+
+            write!(f, "\n    = {} at {}:{}\n    | {}\n    | {indent}{underline}", related.label, related.row, related.column, related.source_line)?;
+        }
+
+        if let Some(note) = &self.note {
+
+            write!(f, "\n    = note: {note}")?;
+        }
+
+        Ok(())
     }
 }
 
 /// Allows for errors to be accumulated and outputted all in one go in a compiler-like fashion
 pub struct Errors {
 
-    errors:Vec<Error>
+    errors: Vec<Error>
 }
 
 impl Errors {
@@ -33,13 +92,41 @@ impl Errors {
     }
 
     /// Append an Error object to the list of errors
-    pub fn record_error(&mut self, row: usize, column: usize, error_type: &'static str, error_message: &'static str) {
+    pub fn record_error(&mut self, row: usize, column: usize, error_type: &'static str, error_message: impl Into<String>) {
+
+        self.errors.push(Error { row, column, error_type, error_message: error_message.into(), source_line: None, span: 1, note: None, related: None })
+    }
+
+    /// Append an Error object to the list of errors, additionally anchoring it to the offending source line
+    /// `span` is the number of columns, starting at `column`, that the caret underline should cover
+    pub fn record_error_spanned(&mut self, row: usize, column: usize, error_type: &'static str, error_message: impl Into<String>, source_line: impl Into<String>, span: usize) {
 
-        self.errors.push(Error { row, column, error_type, error_message })
+        self.errors.push(Error { row, column, error_type, error_message: error_message.into(), source_line: Some(source_line.into()), span, note: None, related: None })
     }
 
-    /// If there are errors, will output all errors and exit the program
-    pub fn get_errors(&self, header: &str) {
+    /// Append an Error object to the list of errors, anchored to the offending source line and with a trailing
+    /// help/note suggestion, e.g. the nearest in-scope symbol name for an undeclared label
+    pub fn record_error_noted(&mut self, row: usize, column: usize, error_type: &'static str, error_message: impl Into<String>, span: Span, note: impl Into<String>) {
+
+        self.errors.push(Error { row, column, error_type, error_message: error_message.into(), source_line: Some(span.source_line), span: span.span, note: Some(note.into()), related: None })
+    }
+
+    /// Append an Error object to the list of errors, anchored to the offending source line, plus a second
+    /// labelled span pointing at a related token elsewhere (e.g. the previous token in a two-token parser error,
+    /// or the opening `{` of a scope reported as never closed)
+    #[allow(clippy::too_many_arguments)]
+    pub fn record_error_related(&mut self, row: usize, column: usize, error_type: &'static str, error_message: impl Into<String>, source_line: impl Into<String>, span: usize,
+                                related_row: usize, related_column: usize, related_label: impl Into<String>, related_source_line: impl Into<String>, related_span: usize) {
+
+        self.errors.push(Error {
+            row, column, error_type, error_message: error_message.into(), source_line: Some(source_line.into()), span, note: None,
+            related: Some(RelatedSpan { row: related_row, column: related_column, label: related_label.into(), source_line: related_source_line.into(), span: related_span })
+        })
+    }
+
+    /// If there are errors, will output all errors under the given header
+    /// Returns the number of errors recorded; the caller decides whether and how to terminate
+    pub fn get_errors(&self, header: &str) -> usize {
 
         if !self.errors.is_empty() {
 
@@ -49,9 +136,9 @@ impl Errors {
 
                 eprintln!("{error}");
             }
-
-            process::exit(sysdefaults::EXIT_CODE);
         }
+
+        self.errors.len()
     }
 }
 
@@ -93,6 +180,12 @@ pub mod errormessages {
     pub const INVALID_VALUE: ErrorMessage = ErrorMessage { error_type: errortypes::INVALID_VALUE, error_message: "A value can only contain digits" };
     pub const INVALID_LABEL: ErrorMessage = ErrorMessage { error_type: errortypes::INVALID_LABEL, error_message: "A Label can only contain letters, digits and underscores" };
 
+    // Expander Errors:
+    pub const MALFORMED_INCLUDE: ErrorMessage = ErrorMessage { error_type: errortypes::SYNTAX, error_message: "include must be followed by a \"quoted path\"" };
+    pub const CIRCULAR_INCLUDE: ErrorMessage  = ErrorMessage { error_type: errortypes::SYNTAX, error_message: "File includes itself, directly or through another file" };
+    pub const INCLUDE_NOT_FOUND: ErrorMessage = ErrorMessage { error_type: errortypes::SYNTAX, error_message: "Could not read the included file" };
+    pub const MALFORMED_MACRO: ErrorMessage   = ErrorMessage { error_type: errortypes::SYNTAX, error_message: "macro must take the form: macro NAME value end" };
+
     // Instruction Pool Errors:
     pub const PROC_TO_BRANCH_REDECL: ErrorMessage   = ErrorMessage { error_type: errortypes::BRANCH_LABEL  , error_message: "Attempting to redeclare a procedure label to a branch label" };
     pub const DUPLICATE_BRANCH: ErrorMessage        = ErrorMessage { error_type: errortypes::BRANCH_LABEL  , error_message: "Duplicate branch label found" };
@@ -111,4 +204,5 @@ pub mod errormessages {
     pub const NON_REGISTER_INP_OPERAND: ErrorMessage         = ErrorMessage { error_type: errortypes::INVALID_OPERANDS       , error_message: "INP instruction operand must be a register" };
     pub const IMMEDIATE_MODE: ErrorMessage                   = ErrorMessage { error_type: errortypes::INVALID_OPERANDS       , error_message: "Source operand of target instruction cannot be addressed in immediate mode" };
     pub const NON_REGISTER_DESTINATION_OPERAND: ErrorMessage = ErrorMessage { error_type: errortypes::INVALID_OPERANDS       , error_message: "Destination operand must be a register" };
+    pub const OPERAND_OUT_OF_RANGE: ErrorMessage             = ErrorMessage { error_type: errortypes::INVALID_OPERANDS       , error_message: "Operand value is outside the representable range" };
 }
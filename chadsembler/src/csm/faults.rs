@@ -0,0 +1,42 @@
+use std::fmt;
+
+/// A recoverable runtime condition raised while executing a single instruction
+/// Allows the VM core to be embedded and unit-tested instead of calling `std::process::exit`
+#[derive(Debug, PartialEq)]
+pub enum Fault {
+
+    InvalidInput,
+    DivideByZero,
+    MemoryOutOfBounds(isize),
+    InstructionLimitReached,
+    InvalidOpcode(usize),
+    StackOverflow,
+    InvalidSyscall(isize),
+    CapabilityDenied
+}
+
+impl fmt::Display for Fault {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+
+        match self {
+
+            Self::InvalidInput => write!(f, "Invalid Input Fault -> Input could not be interpreted as an integer"),
+            Self::DivideByZero => write!(f, "Divide By Zero Fault -> Attempted to divide or modulo by zero"),
+            Self::MemoryOutOfBounds(address) => write!(f, "Memory Out Of Bounds Fault -> Attempted to access memory address {address}"),
+            Self::InstructionLimitReached => write!(f, "Instruction Limit Reached Fault -> Exceeded the configured instruction budget"),
+            Self::InvalidOpcode(opcode) => write!(f, "Invalid Opcode Fault -> {opcode} does not correspond to an instruction"),
+            Self::StackOverflow => write!(f, "Stack Overflow Fault -> The call stack has no room left"),
+            Self::InvalidSyscall(number) => write!(f, "Invalid Syscall Fault -> {number} is not a recognised syscall, or its arguments were invalid"),
+            Self::CapabilityDenied => write!(f, "Capability Denied Fault -> The embedder did not grant access to that resource")
+        }
+    }
+}
+
+/// Distinguishes whether the run loop should continue onto the next instruction or stop
+#[derive(Debug, PartialEq)]
+pub enum ControlFlow {
+
+    Continue,
+    Halt
+}
@@ -1,340 +1,641 @@
-#![allow(non_snake_case, unused_variables)]
-
-use std::io::{self, Write};
-use std::process::exit;
-
-use crate::csm::binarystring;
-use crate::csm::structs::{Memory, MemoryValue};
-use crate::csm::architecture::registers;
-use crate::csm::defaults::sysdefaults;
-
-const ZERO: isize = '0' as isize;
-
-pub struct MachineOperations<'a> {
-
-    // PUBLIC so Memory can still be accessed while borrowed by the object
-    pub memory: &'a mut Memory,
-
-    program_counter_address: isize,
-    flags_register_address: isize,
-    return_register_address: isize,
-
-    buffer: String,
-    stdin: io::Stdin,
-    stdout: io::Stdout    
-}
-
-impl<'a> MachineOperations<'a> {
-
-    /// Constructor for a 'MachineOperations' object
-    pub fn new(memory: &'a mut Memory, gprs: usize) -> Self {
-
-        MachineOperations {
-            memory,
-            program_counter_address: (registers::PROGRAM_COUNTER.offset + gprs) as isize * -1,
-            flags_register_address:  (registers::FLAGS_REGISTER.offset  + gprs) as isize * -1,
-            return_register_address: (registers::RETURN_REGISTER.offset + gprs) as isize * -1,
-
-            // Fields used for caching
-            buffer: String::new(),
-            stdin: io::stdin(),
-            stdout: io::stdout()
-        }
-    }
-
-    /// 0 Operands
-    /// Suspends the execution of the program
-    fn HLT(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        std::process::exit(sysdefaults::EXIT_CODE);
-    }
-
-    /// 2 Operands
-    /// Add the value in the source operand onto the value in the destination operand
-    fn ADD(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_value(destination.address, destination.value + source.value);
-    }
-
-    /// 2 Operands
-    /// Subtract the value in the source operand from the value in the destination operand
-    fn SUB(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_value(destination.address, destination.value - source.value);
-    }
-
-    /// 2 Operands
-    /// Store the value in the destination operand into the source operand
-    fn STA(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_binary(source.address, destination.bits);
-    }
-
-    /// 0 Operands
-    /// Perform an empty operation, do nothing - wastes a clock cycle
-    fn NOP(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        // Performs no operation(s)
-    }
-
-    /// 2 Operands
-    /// Load the value in the source operand onto the destination operand
-    fn LDA(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_binary(destination.address, source.bits);
-    }
-
-    /// 2 Operands
-    /// Always branch to the address in the source operand, regardless what value is in the destination operand
-    fn BRA(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_value(self.program_counter_address, source.address);
-    }
-
-    /// 2 Operands
-    /// Branch to the address in the source operand if the value in the destination operand == 0
-    fn BRZ(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        if destination.value == 0 {
-            
-            self.memory.insert_value(self.program_counter_address, source.address);
-        }
-    }
-
-    /// 2 Operands
-    /// Branch to the address in the source operand if the value in the destination operand >= 0
-    fn BRP(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        if destination.value >= 0 {
-            
-            self.memory.insert_value(self.program_counter_address, source.address);
-        }
-    }
-
-    /// 1 Operand
-    /// Get and store integer input in address in the source operand
-    fn INP(&mut self, source: MemoryValue, destination: MemoryValue) {
-        
-        self.buffer.clear();
-
-        print!(">>>");
-
-        // Flush the buffer to synchronise the output and ensure it is sequential
-        self.stdout.flush().expect("Runtime Error: Failed to display output");
-
-        if self.stdin.read_line(&mut self.buffer).is_err() {
-
-            eprintln!("Runtime Error: Failed to get input");
-            exit(sysdefaults::EXIT_CODE);
-        }
-
-        let value = match self.buffer.trim().parse() {
-
-            Ok(v) => v,
-            Err(_) => {
-
-                eprintln!("Runtime Error: Input could not be interpreted as an integer");
-                exit(sysdefaults::EXIT_CODE);
-            }
-        };
-
-        self.memory.insert_value(source.address, value);
-    }
-
-    /// 1 Operand
-    /// Output the value in source the source operand
-    fn OUT(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        println!("{}", source.value);
-
-        // Flush the buffer to synchronise the output and ensure it is sequential
-        self.stdout.flush().expect("Runtime Error: Failed to display output");
-    }
-
-    /// 1 Operand
-    /// Output the value in the source operand encoded as a character
-    fn OUTC(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        print!("{}", source.value as u8 as char);
-
-        // Flush the buffer to synchronise the output and ensure it is sequential
-        self.stdout.flush().expect("Runtime Error: Failed to display output");
-    }
-
-    /// 1 Operand
-    /// Output the bits in the source operand
-    fn OUTB(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        println!("{}", source.bits);
-
-        // Flush the buffer to synchronise the output and ensure it is sequential
-        self.stdout.flush().expect("Runtime Error: Failed to display output");
-    }
-
-    /// 2 Operands
-    /// Bitwise AND on the destination operand with a mask of the source operand
-    fn AND(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_binary(destination.address, 
-                                  binarystring::bitwise_and(&source.bits, &destination.bits))
-    }
-
-    /// 2 Operands
-    /// Bitwise OR on the destination operand with a mask of the source operand
-    fn OR(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_binary(destination.address, 
-                                  binarystring::bitwise_or(&source.bits, &destination.bits))
-    }
-
-    /// 2 Operands
-    /// Bitwise NOT on the source operand with the result stored in the destination operand
-    fn NOT(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_binary(destination.address, 
-                                  binarystring::bitwise_not(&source.bits));
-    }
-
-    /// 2 Operands
-    /// Bitwise XOR on the destination operand with a mask of the source operand
-    fn XOR(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_binary(destination.address, 
-                                  binarystring::bitwise_xor(&source.bits, &destination.bits))
-    }
-
-    /// 2 Operands
-    /// Bitwise Logical Left Shift on the destination operand N times where N is the value in the source operand
-    fn LSL(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        if let Some((carry, shift)) = binarystring::logical_shift_left(&destination.bits, source.value) {
-
-            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO);
-
-            self.memory.insert_binary(destination.address, shift);
-        }
-    }
-
-    /// 2 Operands
-    /// Bitwise Logical Right Shift on the destination operand N times where N is the value in the source operand
-    fn LSR(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        if let Some((carry, shift)) = binarystring::logical_shift_right(&destination.bits, source.value) {
-
-            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO);
-
-            self.memory.insert_binary(destination.address, shift);
-        }
-    }
-
-    /// 2 Operands
-    /// Bitwise Arithmetic Left Shift on the destination operand N times where N is the value in the source operand
-    fn ASL(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        if let Some((carry, shift)) = binarystring::arithmetic_shift_left(&destination.bits, source.value) {
-
-            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO);
-
-            self.memory.insert_binary(destination.address, shift);
-        }
-    }
-
-    /// 2 Operands
-    /// Bitwise Arithmetic Right Shift on the destination operand N times where N is the value in the source operand
-    fn ASR(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        if let Some((carry, shift)) = binarystring::arithmetic_shift_right(&destination.bits, source.value) {
-
-            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO);
-
-            self.memory.insert_binary(destination.address, shift);
-        }
-    }
-
-    /// 2 Operands
-    /// Bitwise Circular Left Shift on the destination operand N times where N is the value in the source operand
-    fn CSL(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        if let Some(shift) = binarystring::circular_shift_left(&destination.bits, source.value) {
-
-            self.memory.insert_binary(destination.address, shift);
-        }
-    }
-
-    /// 2 Operands
-    /// Bitwise Circular Right Shift on the destination operand N times where N is the value in the source operand
-    fn CSR(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        if let Some(shift) = binarystring::circular_shift_right(&destination.bits, source.value) {
-
-            self.memory.insert_binary(destination.address, shift);
-        }
-    }
-
-    /// 2 Operands
-    /// Bitwise Circular Left Shift with Carry on the destination operand N times where N is the value in the source operand
-    fn CSLC(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        let flags = self.memory.get(self.flags_register_address);
-
-
-        if let Some((carry, shift)) = binarystring::circular_shift_left_carry(&destination.bits, &flags[flags.len()..], source.value) {
-
-            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO);
-
-            self.memory.insert_binary(destination.address, shift);
-        }
-    }
-
-    /// 2 Operands
-    /// Bitwise Circular Right Shift with Carry on the destination operand N times where N is the value in the source operand
-    fn CSRC(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        let flags = self.memory.get(self.flags_register_address);
-
-
-        if let Some((carry, shift)) = binarystring::circular_shift_right_carry(&destination.bits, &flags[flags.len()..], source.value) {
-
-            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO);
-
-            self.memory.insert_binary(destination.address, shift);
-        }
-    }
-
-    /// 1 Operand - Invoke the address held in the source operand
-    // The RR is updated to store the current address
-    fn CALL(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_binary(self.return_register_address, 
-                                  self.memory.get(self.program_counter_address));
-
-        self.memory.insert_value(self.program_counter_address, source.address);
-    }
-
-    /// 0 Operands
-    /// Returns from a procedure by setting the PC to the value in the RR
-    fn RET(&mut self, source: MemoryValue, destination: MemoryValue) {
-
-        self.memory.insert_binary(self.program_counter_address, 
-                                  self.memory.get(self.return_register_address));
-    }
-
-    /// Will perform the operation associated with a given opcode and on the source and destination values
-    pub fn execute(&mut self, opcode: usize, source: MemoryValue, destination: MemoryValue) {
-
-        let machine_operations = [
-            MachineOperations::HLT,  MachineOperations::ADD,  MachineOperations::SUB, MachineOperations::STA,
-            MachineOperations::NOP,  MachineOperations::LDA,  MachineOperations::BRA, MachineOperations::BRZ,
-            MachineOperations::BRP,  MachineOperations::INP,  MachineOperations::OUT, MachineOperations::OUTC,
-            MachineOperations::OUTB, MachineOperations::AND,  MachineOperations::OR,  MachineOperations::NOT,
-            MachineOperations::XOR,  MachineOperations::LSL,  MachineOperations::LSR, MachineOperations::ASL,
-            MachineOperations::ASR,  MachineOperations::CSL,  MachineOperations::CSR, MachineOperations::CSLC,
-            MachineOperations::CSRC, MachineOperations::CALL, MachineOperations::RET
-        ];
-
-        // Each instruction takes a source and destination operand regardless how many operands they use
-        // This uniformity allows for each the instructions to be looked up and instantly executed
-        //      without a need to check for edge cases
-        machine_operations[opcode](self, source, destination);
-    }
-}
+#![allow(non_snake_case, unused_variables)]
+
+use std::io::{self, Write};
+
+use crate::csm::binarystring;
+use crate::csm::structs::{Memory, MemoryValue};
+use crate::csm::architecture::{instructions, registers};
+use crate::csm::capabilities::{self, Capabilities};
+use crate::csm::faults::{ControlFlow, Fault};
+
+const ZERO: isize = '0' as isize;
+
+pub struct MachineOperations<'a> {
+
+    // PUBLIC so Memory can still be accessed while borrowed by the object
+    pub memory: &'a mut Memory,
+
+    program_counter_address: isize,
+    flags_register_address: isize,
+    stack_pointer_address: isize,
+    accumulator_address: isize,
+
+    // `None` leaves the instruction budget unlimited
+    max_cycles: Option<u64>,
+    cycles: u64,
+
+    // What `SYSCALL` is allowed to open on the host's filesystem
+    capabilities: Capabilities,
+
+    buffer: String,
+    stdin: io::Stdin,
+    stdout: io::Stdout
+}
+
+impl<'a> MachineOperations<'a> {
+
+    /// Constructor for a 'MachineOperations' object
+    /// `max_cycles` bounds the number of instructions `execute` will run before raising an `InstructionLimitReached` fault, pass `None` for no limit
+    /// The stack pointer is initialised to the highest address in memory, so `CALL`/`PUSH` grow the stack
+    /// downwards towards the ascending program/data region
+    /// `capabilities` is the entire filesystem grant `SYSCALL` is allowed to act on
+    pub fn new(memory: &'a mut Memory, gprs: usize, max_cycles: Option<u64>, capabilities: Capabilities) -> Self {
+
+        let stack_pointer_address = (registers::STACK_POINTER.offset + gprs) as isize * -1;
+
+        memory.insert_value(stack_pointer_address, memory.highest_address())
+              .expect("stack pointer address is always valid");
+
+        MachineOperations {
+            memory,
+            program_counter_address: (registers::PROGRAM_COUNTER.offset + gprs) as isize * -1,
+            flags_register_address:  (registers::FLAGS_REGISTER.offset  + gprs) as isize * -1,
+            stack_pointer_address,
+            accumulator_address: (registers::ACCUMULATOR.offset + gprs) as isize * -1,
+
+            max_cycles,
+            cycles: 0,
+
+            capabilities,
+
+            // Fields used for caching
+            buffer: String::new(),
+            stdin: io::stdin(),
+            stdout: io::stdout()
+        }
+    }
+
+    /// Return the number of instructions executed so far
+    pub fn cycles(&self) -> u64 {
+
+        self.cycles
+    }
+
+    /// 0 Operands
+    /// Suspends the execution of the program
+    fn HLT(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        Ok(ControlFlow::Halt)
+    }
+
+    /// 2 Operands
+    /// Add the value in the source operand onto the value in the destination operand
+    /// Sets the flags register to the carry-out and signed-overflow of the addition, mirroring the shift instructions
+    fn ADD(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        let (carry, overflow) = binarystring::add_carry_overflow(destination.value, source.value, destination.bits.len() as isize);
+
+        self.memory.insert_value(self.flags_register_address, carry as isize | (overflow as isize) << 1)?;
+        self.memory.insert_value(destination.address, destination.value + source.value)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Subtract the value in the source operand from the value in the destination operand
+    /// Sets the flags register to the carry-out and signed-overflow of the subtraction, mirroring the shift instructions
+    fn SUB(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        let (carry, overflow) = binarystring::add_carry_overflow(destination.value, -source.value, destination.bits.len() as isize);
+
+        self.memory.insert_value(self.flags_register_address, carry as isize | (overflow as isize) << 1)?;
+        self.memory.insert_value(destination.address, destination.value - source.value)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Store the value in the destination operand into the source operand
+    fn STA(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        self.memory.insert_binary(source.address, destination.bits)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 0 Operands
+    /// Perform an empty operation, do nothing - wastes a clock cycle
+    fn NOP(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        // Performs no operation(s)
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Load the value in the source operand onto the destination operand
+    fn LDA(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        self.memory.insert_binary(destination.address, source.bits)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Always branch to the address in the source operand, regardless what value is in the destination operand
+    fn BRA(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        self.memory.insert_value(self.program_counter_address, source.address)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Branch to the address in the source operand if the value in the destination operand == 0
+    fn BRZ(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if destination.value == 0 {
+
+            self.memory.insert_value(self.program_counter_address, source.address)?;
+        }
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Branch to the address in the source operand if the value in the destination operand >= 0
+    fn BRP(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if destination.value >= 0 {
+
+            self.memory.insert_value(self.program_counter_address, source.address)?;
+        }
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 1 Operand
+    /// Get and store integer input in address in the source operand
+    fn INP(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        self.buffer.clear();
+
+        print!(">>>");
+
+        // Flush the buffer to synchronise the output and ensure it is sequential
+        self.stdout.flush().expect("Runtime Error: Failed to display output");
+
+        if self.stdin.read_line(&mut self.buffer).is_err() {
+
+            return Err(Fault::InvalidInput);
+        }
+
+        let value = match self.buffer.trim().parse() {
+
+            Ok(v) => v,
+            Err(_) => return Err(Fault::InvalidInput)
+        };
+
+        self.memory.insert_value(source.address, value)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 1 Operand
+    /// Output the value in source the source operand
+    fn OUT(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        println!("{}", source.value);
+
+        // Flush the buffer to synchronise the output and ensure it is sequential
+        self.stdout.flush().expect("Runtime Error: Failed to display output");
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 1 Operand
+    /// Output the value in the source operand encoded as a character
+    fn OUTC(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        print!("{}", source.value as u8 as char);
+
+        // Flush the buffer to synchronise the output and ensure it is sequential
+        self.stdout.flush().expect("Runtime Error: Failed to display output");
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 1 Operand
+    /// Output the bits in the source operand
+    fn OUTB(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        println!("{}", source.bits);
+
+        // Flush the buffer to synchronise the output and ensure it is sequential
+        self.stdout.flush().expect("Runtime Error: Failed to display output");
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise AND on the destination operand with a mask of the source operand
+    fn AND(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        self.memory.insert_binary(destination.address,
+                                  binarystring::bitwise_and(&source.bits, &destination.bits))?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise OR on the destination operand with a mask of the source operand
+    fn OR(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        self.memory.insert_binary(destination.address,
+                                  binarystring::bitwise_or(&source.bits, &destination.bits))?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise NOT on the source operand with the result stored in the destination operand
+    fn NOT(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        self.memory.insert_binary(destination.address,
+                                  binarystring::bitwise_not(&source.bits))?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise XOR on the destination operand with a mask of the source operand
+    fn XOR(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        self.memory.insert_binary(destination.address,
+                                  binarystring::bitwise_xor(&source.bits, &destination.bits))?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise Logical Left Shift on the destination operand N times where N is the value in the source operand
+    fn LSL(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if let Some((carry, shift)) = binarystring::logical_shift_left(&destination.bits, source.value) {
+
+            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO)?;
+
+            self.memory.insert_binary(destination.address, shift)?;
+        }
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise Logical Right Shift on the destination operand N times where N is the value in the source operand
+    fn LSR(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if let Some((carry, shift)) = binarystring::logical_shift_right(&destination.bits, source.value) {
+
+            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO)?;
+
+            self.memory.insert_binary(destination.address, shift)?;
+        }
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise Arithmetic Left Shift on the destination operand N times where N is the value in the source operand
+    fn ASL(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if let Some((carry, shift)) = binarystring::arithmetic_shift_left(&destination.bits, source.value) {
+
+            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO)?;
+
+            self.memory.insert_binary(destination.address, shift)?;
+        }
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise Arithmetic Right Shift on the destination operand N times where N is the value in the source operand
+    fn ASR(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if let Some((carry, shift)) = binarystring::arithmetic_shift_right(&destination.bits, source.value) {
+
+            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO)?;
+
+            self.memory.insert_binary(destination.address, shift)?;
+        }
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise Circular Left Shift on the destination operand N times where N is the value in the source operand
+    fn CSL(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if let Some(shift) = binarystring::circular_shift_left(&destination.bits, source.value) {
+
+            self.memory.insert_binary(destination.address, shift)?;
+        }
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise Circular Right Shift on the destination operand N times where N is the value in the source operand
+    fn CSR(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if let Some(shift) = binarystring::circular_shift_right(&destination.bits, source.value) {
+
+            self.memory.insert_binary(destination.address, shift)?;
+        }
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise Circular Left Shift with Carry on the destination operand N times where N is the value in the source operand
+    fn CSLC(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        let flags = self.memory.get(self.flags_register_address)?;
+
+
+        if let Some((carry, shift)) = binarystring::circular_shift_left_carry(&destination.bits, &flags[flags.len()-1..], source.value) {
+
+            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO)?;
+
+            self.memory.insert_binary(destination.address, shift)?;
+        }
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Bitwise Circular Right Shift with Carry on the destination operand N times where N is the value in the source operand
+    fn CSRC(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        let flags = self.memory.get(self.flags_register_address)?;
+
+
+        if let Some((carry, shift)) = binarystring::circular_shift_right_carry(&destination.bits, &flags[flags.len()-1..], source.value) {
+
+            self.memory.insert_value(self.flags_register_address, carry as isize - ZERO)?;
+
+            self.memory.insert_binary(destination.address, shift)?;
+        }
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 1 Operand - Invoke the address held in the source operand
+    /// Pushes the current PC onto the stack and decrements the stack pointer, so nested/recursive calls
+    /// each get their own saved return address instead of clobbering a single RR
+    fn CALL(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        let stack_pointer = binarystring::read_signed_int(&self.memory.get(self.stack_pointer_address)?).unwrap();
+        let new_stack_pointer = stack_pointer - 1;
+
+        if new_stack_pointer < 0 {
+
+            return Err(Fault::StackOverflow);
+        }
+
+        self.memory.insert_binary(new_stack_pointer, self.memory.get(self.program_counter_address)?)?;
+        self.memory.insert_value(self.stack_pointer_address, new_stack_pointer)?;
+
+        self.memory.insert_value(self.program_counter_address, source.address)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 0 Operands
+    /// Returns from a procedure by popping the saved return address off the stack into the PC, incrementing
+    /// the stack pointer back past it
+    fn RET(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        let stack_pointer = binarystring::read_signed_int(&self.memory.get(self.stack_pointer_address)?).unwrap();
+
+        self.memory.insert_binary(self.program_counter_address, self.memory.get(stack_pointer)?)?;
+        self.memory.insert_value(self.stack_pointer_address, stack_pointer + 1)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 1 Operand
+    /// Push the value in the source operand onto the top of the stack, decrementing the stack pointer
+    fn PUSH(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        let stack_pointer = binarystring::read_signed_int(&self.memory.get(self.stack_pointer_address)?).unwrap();
+        let new_stack_pointer = stack_pointer - 1;
+
+        if new_stack_pointer < 0 {
+
+            return Err(Fault::StackOverflow);
+        }
+
+        self.memory.insert_value(new_stack_pointer, source.value)?;
+        self.memory.insert_value(self.stack_pointer_address, new_stack_pointer)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 1 Operand
+    /// Pop the value off the top of the stack into the source operand (a register), incrementing the stack pointer
+    fn POP(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        let stack_pointer = binarystring::read_signed_int(&self.memory.get(self.stack_pointer_address)?).unwrap();
+
+        let value = self.memory.get(stack_pointer)?;
+        self.memory.insert_binary(source.address, value)?;
+
+        self.memory.insert_value(self.stack_pointer_address, stack_pointer + 1)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Multiply the value in the destination operand by the value in the source operand
+    fn MUL(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        self.memory.insert_value(destination.address, destination.value * source.value)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Divide the value in the destination operand by the value in the source operand, storing the quotient
+    fn DIV(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if source.value == 0 {
+
+            return Err(Fault::DivideByZero);
+        }
+
+        self.memory.insert_value(destination.address, destination.value / source.value)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// 2 Operands
+    /// Divide the value in the destination operand by the value in the source operand, storing the remainder
+    fn MOD(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if source.value == 0 {
+
+            return Err(Fault::DivideByZero);
+        }
+
+        self.memory.insert_value(destination.address, destination.value % source.value)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// Read a NUL-terminated sequence of character-valued words starting at `address` into a Rust `String`,
+    /// mirroring the single-word-per-character convention `OUTC` already uses for terminal output
+    fn read_cstring(&self, address: isize) -> Result<String, Fault> {
+
+        let mut string = String::new();
+        let mut address = address;
+
+        loop {
+
+            let value = binarystring::read_signed_int(&self.memory.get(address)?).unwrap();
+
+            if value == 0 {
+
+                break;
+            }
+
+            string.push(char::from_u32(value as u32).unwrap_or('?'));
+            address += 1;
+        }
+
+        Ok(string)
+    }
+
+    /// Validate a syscall's length argument before it is cast to `usize`, rejecting a negative value (which
+    /// would otherwise wrap into an enormous `usize` and blow up the `Vec` allocation it sizes) and a value
+    /// bigger than the memory pool could ever hold
+    fn valid_length(&self, value: isize, syscall_number: isize) -> Result<usize, Fault> {
+
+        if value < 0 || value > self.memory.highest_address() {
+
+            return Err(Fault::InvalidSyscall(syscall_number));
+        }
+
+        Ok(value as usize)
+    }
+
+    /// Read `length` character-valued words starting at `address` into raw bytes
+    fn read_bytes(&self, address: isize, length: usize) -> Result<Vec<u8>, Fault> {
+
+        let mut bytes = Vec::with_capacity(length);
+
+        for offset in 0..length as isize {
+
+            bytes.push(binarystring::read_signed_int(&self.memory.get(address + offset)?).unwrap() as u8);
+        }
+
+        Ok(bytes)
+    }
+
+    /// Write raw bytes back out as character-valued words starting at `address`
+    fn write_bytes(&mut self, address: isize, bytes: &[u8]) -> Result<(), Fault> {
+
+        for (offset, byte) in bytes.iter().enumerate() {
+
+            self.memory.insert_value(address + offset as isize, *byte as isize)?;
+        }
+
+        Ok(())
+    }
+
+    /// 0 Operands
+    /// Dispatches on the syscall number held in ACC, with up to three arguments conventionally passed in
+    /// REG1/REG2/REG3 (mirroring the syscallN convention real ABIs use to avoid needing one opcode per call),
+    /// and the result written back into ACC
+    /// File syscalls only succeed for paths the embedder granted through `Capabilities`
+    fn SYSCALL(&mut self, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        let syscall_number = binarystring::read_signed_int(&self.memory.get(self.accumulator_address)?).unwrap();
+        let arg1 = binarystring::read_signed_int(&self.memory.get(-1)?).unwrap();
+        let arg2 = binarystring::read_signed_int(&self.memory.get(-2)?).unwrap();
+        let arg3 = binarystring::read_signed_int(&self.memory.get(-3)?).unwrap();
+
+        let result = match syscall_number {
+
+            capabilities::numbers::OPEN => {
+
+                let path = self.read_cstring(arg1)?;
+                self.capabilities.open(&path, arg2)?
+            },
+
+            capabilities::numbers::READ => {
+
+                let length = self.valid_length(arg3, syscall_number)?;
+                let bytes = self.capabilities.read(arg1, length)?;
+                let read = bytes.len() as isize;
+
+                self.write_bytes(arg2, &bytes)?;
+
+                read
+            },
+
+            capabilities::numbers::WRITE => {
+
+                let length = self.valid_length(arg3, syscall_number)?;
+                let bytes = self.read_bytes(arg2, length)?;
+                self.capabilities.write(arg1, &bytes)? as isize
+            },
+
+            capabilities::numbers::CLOSE => {
+
+                self.capabilities.close(arg1)?;
+                0
+            },
+
+            capabilities::numbers::EXIT => {
+
+                self.memory.insert_value(self.accumulator_address, arg1)?;
+
+                return Ok(ControlFlow::Halt);
+            },
+
+            _ => return Err(Fault::InvalidSyscall(syscall_number))
+        };
+
+        self.memory.insert_value(self.accumulator_address, result)?;
+
+        Ok(ControlFlow::Continue)
+    }
+
+    /// Will perform the operation associated with a given opcode and on the source and destination values
+    /// Returns the resulting control flow, or the fault raised by the instruction
+    pub fn execute(&mut self, opcode: usize, source: MemoryValue, destination: MemoryValue) -> Result<ControlFlow, Fault> {
+
+        if let Some(max_cycles) = self.max_cycles {
+
+            if self.cycles >= max_cycles {
+
+                return Err(Fault::InstructionLimitReached);
+            }
+        }
+
+        let operations = Self::operations();
+
+        if opcode >= operations.len() {
+
+            return Err(Fault::InvalidOpcode(opcode));
+        }
+
+        // Wrap rather than panic, the cycle count is a diagnostic, not a correctness guarantee
+        self.cycles = self.cycles.wrapping_add(1);
+
+        // Each instruction takes a source and destination operand regardless how many operands they use
+        // This uniformity allows for each the instructions to be looked up and instantly executed
+        //      without a need to check for edge cases
+        operations[opcode](self, source, destination)
+    }
+
+    // Opcode-ordered dispatch table, generated at build time by `build.rs` from `instructions.in` so it can
+    // never drift out of sync with the opcodes assigned to `csm::architecture::instructions`
+    // Only the array literal is generated: `include!`ing a full `fn` item into an `impl` block doesn't work,
+    // so the body is spliced into this hand-written fn instead
+    fn operations() -> [fn(&mut MachineOperations<'a>, MemoryValue, MemoryValue) -> Result<ControlFlow, Fault>; instructions::NUMBER_INSTRUCTIONS] {
+
+        include!(concat!(env!("OUT_DIR"), "/operations_table.rs"))
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+/// Maps a row of `precompilation::expander`'s merged output back to the file and row within that file it
+/// actually originated from
+/// Splicing an `include`d file's text in place of its directive changes the merged document's line count, so a
+/// token's `row` can no longer be taken at face value once `include`/`macro` have run - this is the single
+/// source of truth later pipeline stages consult to report a diagnostic against the file the user actually wrote
+pub struct SourceMap {
+
+    origins: Vec<(PathBuf, usize)>
+}
+
+impl SourceMap {
+
+    /// Constructor for a 'SourceMap' object
+    pub fn new() -> Self {
+
+        SourceMap { origins: Vec::new() }
+    }
+
+    /// Record that the next row appended to the merged output originates from `path` at `row`
+    pub fn push(&mut self, path: &Path, row: usize) {
+
+        self.origins.push((path.to_owned(), row));
+    }
+
+    /// Append another file's already-built map, e.g. once an `include`d file's own text has been spliced in
+    pub fn extend(&mut self, other: SourceMap) {
+
+        self.origins.extend(other.origins);
+    }
+
+    /// Resolve a row of the merged output to the file and row it originated from
+    /// Falls back to the row unchanged, against an empty path, if the row was never recorded
+    pub fn resolve(&self, row: usize) -> (&Path, usize) {
+
+        match self.origins.get(row.saturating_sub(1)) {
+
+            Some((path, row)) => (path.as_path(), *row),
+            None => (Path::new(""), row)
+        }
+    }
+
+    /// Resolve a row of the merged output the way diagnostics want it: the row to report, plus the originating
+    /// file name, but only when it differs from `main_path` - so a program that never uses `include` reports
+    /// exactly as it always did, with no redundant file name cluttering every message
+    pub fn locate(&self, main_path: &str, row: usize) -> (usize, Option<String>) {
+
+        let (file, resolved_row) = self.resolve(row);
+
+        if file.as_os_str().is_empty() || file == Path::new(main_path) {
+
+            (resolved_row, None)
+
+        } else {
+
+            (resolved_row, Some(file.display().to_string()))
+        }
+    }
+}
@@ -1,16 +1,21 @@
-use std::{{collections::HashMap}, process};
+use std::collections::HashMap;
 use crate::csm::binarystring;
 use crate::csm::tokens::TypedToken;
-use crate::csm::defaults::{SymbolTypes, sysdefaults};
+use crate::csm::defaults::SymbolTypes;
+use crate::csm::faults::Fault;
 
 /// mnemonic: The symbol that represents a machine code operation
 /// opcode: the integer value the mnemonic is encoded to
 /// operands: the number of values an instruction can operate on at most
+/// register_only_operand: the source operand must be register-addressed (e.g. INP)
+/// immediate_disallowed: the source operand cannot be immediate-addressed (e.g. STA, the branches, CALL)
 pub struct Instruction {
 
     pub mnemonic: &'static str,
     pub opcode: isize,
-    pub operands: isize
+    pub operands: isize,
+    pub register_only_operand: bool,
+    pub immediate_disallowed: bool
 }
 
 impl PartialEq for Instruction {
@@ -65,13 +70,60 @@ pub struct Operand<'a> {
     pub operand_value: &'a TypedToken
 }
 
+/// word_index: the position, within the object's own `words`, of the instruction holding the placeholder
+/// is_source: whether the placeholder sits in the source operand field or the destination operand field
+/// symbol: the name of the external symbol that should be patched into the placeholder once it is known
+pub struct Relocation {
+
+    pub word_index: usize,
+    pub is_source: bool,
+    pub symbol: String
+}
+
+/// word_index: the position, within the object's own `words`, of the instruction holding a self-referencing
+/// address
+/// is_source: whether the address sits in the source operand field or the destination operand field
+/// Unlike a `Relocation`, the value is already known (it is a label the object defines itself), but it is only
+/// correct relative to the object's own `words`, starting at 0 - a linker must add the object's load offset to
+/// it once it knows where the object will actually sit in the final image
+pub struct Rebase {
+
+    pub word_index: usize,
+    pub is_source: bool
+}
+
+/// The output of assembling a single Chadsembler source file without resolving references to symbols the
+/// file does not itself define
+/// words: the encoded instruction/variable words, with every external label operand left as a zeroed placeholder
+/// exports: every label the file defines at global scope, mapped to its offset within `words`
+/// relocations: one entry per placeholder in `words`, recording what a linker needs to patch it with
+/// rebases: one entry per operand field already resolved to an address within this object's own `words`
+/// (a label the object defines, referenced either at global or procedure scope) - the linker must shift each
+/// by the object's load offset, the same way `exports` is rebased
+pub struct RelocatableObject {
+
+    pub words: Vec<String>,
+    pub exports: HashMap<String, isize>,
+    pub relocations: Vec<Relocation>,
+    pub rebases: Vec<Rebase>
+}
+
+/// The number of words grouped into a single lazily-allocated page of the sparse memory region
+const PAGE_SIZE: usize = 256;
+
 /// Allows for a secure way to manipulate a pool of memory
+/// Registers sit in a small dense region (addressed negatively) so register access stays O(1); the much larger
+/// positive-addressed memory region is paged and sparse, only allocating a page the first time one of its
+/// addresses is written to, so configuring a huge address space does not eagerly allocate every word of it
 pub struct Memory {
 
     number_registers: usize,
     architecture: usize,
-    memory_pool: Vec<String>,
-    memory_pool_length: usize
+    registers: Vec<String>,
+    pages: HashMap<usize, Box<[String]>>,
+    memory_size: usize,
+    memory_pool_length: usize,
+    default_value: String
 }
 
 impl Memory {
@@ -80,54 +132,100 @@ impl Memory {
     pub fn new(number_registers: usize, architecture: usize, operand_bits: usize) -> Self {
 
         let default_value = "0".repeat(architecture as usize);
-        
+
         //                                            Number Memory Addresses + Number Registers
         let memory_pool_length = 2usize.pow(operand_bits as u32-1) + number_registers;
-        let memory_pool = vec![default_value; memory_pool_length as usize];
+        let memory_size = memory_pool_length - number_registers;
+        let registers = vec![default_value.clone(); number_registers];
 
         Memory {
 
             number_registers,
             architecture,
-            memory_pool,
-            memory_pool_length
+            registers,
+            pages: HashMap::new(),
+            memory_size,
+            memory_pool_length,
+            default_value
         }
     }
 
     /// Calculate the underlying address an abstract address corresponds to
-    fn calculate_address(&self, address: isize) -> usize {
+    /// Validates the address falls within the allocated memory pool (including the negative register region)
+    fn calculate_address(&self, address: isize) -> Result<usize, Fault> {
 
         let pointer = self.number_registers as isize + address;
 
         if pointer > -1 && pointer < self.memory_pool_length as isize {
 
-            return pointer as usize;
+            return Ok(pointer as usize);
         }
 
-        eprintln!("Segmentation Fault: Attempted to access memory address {address}");
-        process::exit(sysdefaults::EXIT_CODE);
+        Err(Fault::MemoryOutOfBounds(address))
+    }
+
+    /// Split a pointer into the page it falls within and its offset into that page
+    /// Only valid for pointers that have already been confirmed to fall within the sparse memory region
+    fn page_location(&self, pointer: usize) -> (usize, usize) {
+
+        let index = pointer - self.number_registers;
+
+        (index / PAGE_SIZE, index % PAGE_SIZE)
     }
 
     /// Get the value at a given memory address
-    pub fn get(&self, address: isize) -> String{
+    /// A never-written page of the sparse region reads back as the zeroed default word, the same value it
+    /// would hold had it been eagerly allocated
+    pub fn get(&self, address: isize) -> Result<String, Fault> {
+
+        let pointer = self.calculate_address(address)?;
 
-        self.memory_pool[self.calculate_address(address)].to_owned()
+        if pointer < self.number_registers {
+
+            return Ok(self.registers[pointer].to_owned());
+        }
+
+        let (page_index, offset) = self.page_location(pointer);
+
+        match self.pages.get(&page_index) {
+
+            Some(page) => Ok(page[offset].to_owned()),
+            None => Ok(self.default_value.to_owned())
+        }
     }
 
     /// Place a binary string into a given memory address
-    pub fn insert_binary(&mut self, address: isize, value: String) {
+    /// Writing into the sparse region fault-allocates its page with zeroed words the first time it is touched
+    pub fn insert_binary(&mut self, address: isize, value: String) -> Result<(), Fault> {
+
+        let pointer = self.calculate_address(address)?;
+
+        if pointer < self.number_registers {
 
-        let address = self.calculate_address(address);
-        self.memory_pool[address] = value;
+            self.registers[pointer] = value;
+            return Ok(());
+        }
+
+        let (page_index, offset) = self.page_location(pointer);
+        let default_value = &self.default_value;
+
+        let page = self.pages.entry(page_index).or_insert_with(|| vec![default_value.clone(); PAGE_SIZE].into_boxed_slice());
+        page[offset] = value;
+
+        Ok(())
     }
 
     /// Place a value (converted to a binary string) into a given memory address
-    pub fn insert_value(&mut self, address: isize, value: isize) {
+    pub fn insert_value(&mut self, address: isize, value: isize) -> Result<(), Fault> {
 
-        let address = self.calculate_address(address);
+        self.insert_binary(address, binarystring::signed_int(value, self.architecture as isize))
+    }
 
-        self.memory_pool[address] = binarystring::signed_int(value, self.architecture as isize);
-        
+    /// The highest non-register-addressed memory address
+    /// Useful for initialising a register that should start near the top of memory, e.g. a stack pointer
+    pub fn highest_address(&self) -> isize {
+
+        (self.memory_size - 1) as isize
     }
 }
 
@@ -203,7 +203,7 @@ pub mod tokenutils {
         let mut minimum = 0;
         
         // Previous checks will ensure this will always correspond to a valid option
-        for (key, default) in [sysdefaults::CLOCK_CONFIG, sysdefaults::REGISTERS_CONFIG, sysdefaults::MEMORY_CONFIG] {
+        for (key, default) in [sysdefaults::CLOCK_CONFIG, sysdefaults::REGISTERS_CONFIG, sysdefaults::MEMORY_CONFIG, sysdefaults::CYCLES_CONFIG] {
 
             if key == option.token_value {
 
@@ -7,13 +7,18 @@ use std::env::args;
 mod csm;
 mod pipeline;
 
-use pipeline::{precompilation, lexer, parser, instructionpools, semanticanalyser, codegenerator, virtualmachine};
+use pipeline::{precompilation, lexer, parser, instructionpools, semanticanalyser, codegenerator, linker, virtualmachine};
 
 use csm::defaults::sysdefaults;
 use crate::csm::architecture::{registers, addressingmodes};
 use crate::csm::defaults::lexerdefaults;
+use crate::csm::disassembler;
+use crate::csm::structs::{Memory, RelocatableObject};
 use crate::csm::tokens::{TokenTypes, TypedToken};
 
+/// Passed as the first argument to print a disassembly listing of an assembled file instead of running it
+const DISASSEMBLE_FLAG: &str = "--disassemble";
+
 /// Read a file into a string
 /// Will read the file using a Universal New Line Sequence
 /// In this case it is a singular '\n'
@@ -47,7 +52,7 @@ fn read_file(path: &str) -> String {
         buffer.shrink_to_fit();
 
         buffer
-    
+
     } else {
 
         eprintln!("Cannot assemble a file that does not exist");
@@ -55,50 +60,93 @@ fn read_file(path: &str) -> String {
     }
 }
 
-fn get_source_code(argv: &Vec<String>) -> String {
+/// Split the arguments following the program name (and the optional `--disassemble` flag) into the source
+/// files to assemble and the config directives to forward to the ArgumentProcessor
+/// Every leading argument that isn't itself a directive (i.e. doesn't start with the directive prefix) is taken
+/// to be a file; the first directive-prefixed argument marks where the config directives begin
+/// More than one source file switches assembly into relocatable-object-plus-linker mode, so a program can be
+/// split across files and `link`ed back together
+fn get_source_paths(arguments: &[String]) -> (&[String], &[String]) {
 
-    let path = match argv.get(1) {
+    let end = arguments.iter()
+        .position(|arg| arg.starts_with(sysdefaults::DIRECTIVE_PREFIX))
+        .unwrap_or(arguments.len());
 
-        Some(p) => p,
-        None => {
+    if end == 0 {
 
-            eprintln!("No file was passed in to be assembled");
-            exit(sysdefaults::EXIT_CODE);
-        }
-    };
+        eprintln!("No file was passed in to be assembled");
+        exit(sysdefaults::EXIT_CODE);
+    }
+
+    for path in &arguments[..end] {
 
-    if !path.ends_with(sysdefaults::CSM_EXTENSION) {
+        if !path.ends_with(sysdefaults::CSM_EXTENSION) {
 
-        eprintln!("Chadsembly Warning: File name does not end with a `.csm` file extension");
+            eprintln!("Chadsembly Warning: File name does not end with a `.csm` file extension");
+        }
     }
 
-    read_file(path)
+    (&arguments[..end], &arguments[end..])
 }
 
-fn main() {
+/// Assemble a single source file all the way down to a final, runnable `Memory` image
+fn assemble(source_path: &str, config_table: &mut HashMap<String, usize>) -> (Memory, (usize, usize, usize, usize)) {
 
-    let mut config_table = HashMap::new();
-    config_table.insert(sysdefaults::MEMORY_CONFIG.0.to_owned(),    sysdefaults::MEMORY_CONFIG.1);  // defaults::MEMORY_CONFIG.1
-    config_table.insert(sysdefaults::REGISTERS_CONFIG.0.to_owned(), sysdefaults::REGISTERS_CONFIG.1);
-    config_table.insert(sysdefaults::CLOCK_CONFIG.0.to_owned(),     sysdefaults::CLOCK_CONFIG.1);
-
-    let arguments: Vec<String> = args().collect();
+    let source_code = read_file(source_path);
 
-    let source_code = get_source_code(&arguments);
-
-    precompilation::argumentprocessor::run(
-        &arguments[2..], 
-        sysdefaults::DIRECTIVE_PREFIX, 
-        sysdefaults::DELIMITER, 
-        &mut config_table
+    let (source_code, source_map) = precompilation::expander::run(
+        &source_code,
+        source_path,
+        sysdefaults::DIRECTIVE_PREFIX,
+        sysdefaults::COMMENT_PREFIX
     );
 
     precompilation::preprocessor::run(
         &source_code,
-        sysdefaults::DIRECTIVE_PREFIX, 
+        sysdefaults::DIRECTIVE_PREFIX,
         sysdefaults::COMMENT_PREFIX,
-        sysdefaults::DELIMITER, 
-        &mut config_table
+        sysdefaults::DELIMITER,
+        config_table
+    );
+
+    let tokens = lexer::run(
+        &source_code,
+        sysdefaults::DIRECTIVE_PREFIX,
+        sysdefaults::COMMENT_PREFIX,
+    );
+
+    parser::run(&tokens, &source_code, source_path, &source_map);
+
+    let (mut global_scope, mut procedure_scopes) = instructionpools::run(&tokens, &source_code, source_path, &source_map);
+
+    let default_acc = TypedToken { token_type: TokenTypes::Register, token_value: registers::ACCUMULATOR.register.to_owned(), row: 0, column: 0 };
+    let default_register = TypedToken { token_type: TokenTypes::AddressingMode, token_value: addressingmodes::REGISTER.symbol.to_owned(), row: 0, column: 0 };
+    let default_direct = TypedToken { token_type: TokenTypes::AddressingMode, token_value: addressingmodes::DIRECT.symbol.to_owned(), row: 0, column: 0 };
+    let default_separator = TypedToken { token_type: TokenTypes::Separator, token_value: lexerdefaults::SEPARATOR.to_string(), row: 0, column: 0 };
+
+    let default_operands = (&default_acc, &default_register, &default_direct, &default_separator);
+
+    semanticanalyser::run(&mut global_scope, &mut procedure_scopes, &default_operands, config_table, &source_code);
+
+    let (memory, machine_operation_bits, addressing_mode_bits, operand_bits) = codegenerator::run(&mut global_scope, &mut procedure_scopes, config_table);
+
+    (memory, (machine_operation_bits, addressing_mode_bits, operand_bits, machine_operation_bits+addressing_mode_bits+2*operand_bits))
+}
+
+/// Assemble a single source file into a `RelocatableObject` rather than a final `Memory` image, for linking
+/// against the other files passed on the command line
+/// Unlike `assemble`, `config_table` is shared read-only across every file being linked: letting one file's
+/// in-source directives resize memory/registers out from under the others would size their objects
+/// inconsistently, so the machine's shape for a multi-file build comes from the command line alone
+fn assemble_relocatable(source_path: &str, config_table: &HashMap<String, usize>) -> (RelocatableObject, usize, usize, usize) {
+
+    let source_code = read_file(source_path);
+
+    let (source_code, source_map) = precompilation::expander::run(
+        &source_code,
+        source_path,
+        sysdefaults::DIRECTIVE_PREFIX,
+        sysdefaults::COMMENT_PREFIX
     );
 
     let tokens = lexer::run(
@@ -107,9 +155,9 @@ fn main() {
         sysdefaults::COMMENT_PREFIX,
     );
 
-    parser::run(&tokens);
+    parser::run(&tokens, &source_code, source_path, &source_map);
 
-    let (mut global_scope, mut procedure_scopes) = instructionpools::run(&tokens);
+    let (mut global_scope, mut procedure_scopes) = instructionpools::run(&tokens, &source_code, source_path, &source_map);
 
     let default_acc = TypedToken { token_type: TokenTypes::Register, token_value: registers::ACCUMULATOR.register.to_owned(), row: 0, column: 0 };
     let default_register = TypedToken { token_type: TokenTypes::AddressingMode, token_value: addressingmodes::REGISTER.symbol.to_owned(), row: 0, column: 0 };
@@ -118,12 +166,75 @@ fn main() {
 
     let default_operands = (&default_acc, &default_register, &default_direct, &default_separator);
 
-    semanticanalyser::run(&mut global_scope, &mut procedure_scopes, &default_operands);    
+    semanticanalyser::run(&mut global_scope, &mut procedure_scopes, &default_operands, config_table, &source_code);
+
+    codegenerator::run_relocatable(&mut global_scope, &mut procedure_scopes, config_table)
+}
+
+/// Assemble every source file as a relocatable object and link them into a single `Memory` image, in argument order
+fn link_relocatable(source_paths: &[String], config_table: &HashMap<String, usize>) -> (Memory, (usize, usize, usize, usize)) {
+
+    let number_gprs = config_table[sysdefaults::REGISTERS_CONFIG.0];
+    let number_registers = number_gprs + registers::NUMBER_SP_REGISTERS;
+
+    let mut objects = Vec::with_capacity(source_paths.len());
+    let mut bits = (0, 0, 0);
+
+    for source_path in source_paths {
+
+        let (object, machine_operation_bits, addressing_mode_bits, operand_bits) = assemble_relocatable(source_path, config_table);
+
+        bits = (machine_operation_bits, addressing_mode_bits, operand_bits);
+        objects.push(object);
+    }
+
+    let memory = linker::link(objects, bits, number_registers);
+
+    (memory, (bits.0, bits.1, bits.2, bits.0+bits.1+2*bits.2))
+}
+
+fn main() {
+
+    let mut config_table = HashMap::new();
+    config_table.insert(sysdefaults::MEMORY_CONFIG.0.to_owned(),    sysdefaults::MEMORY_CONFIG.1);  // defaults::MEMORY_CONFIG.1
+    config_table.insert(sysdefaults::REGISTERS_CONFIG.0.to_owned(), sysdefaults::REGISTERS_CONFIG.1);
+    config_table.insert(sysdefaults::CLOCK_CONFIG.0.to_owned(),     sysdefaults::CLOCK_CONFIG.1);
+    config_table.insert(sysdefaults::CYCLES_CONFIG.0.to_owned(),    sysdefaults::CYCLES_CONFIG.1);
+
+    let arguments: Vec<String> = args().collect();
 
-    let (mut memory, machine_operation_bits, addressing_mode_bits, operand_bits) = codegenerator::run(&mut global_scope, &mut procedure_scopes, &config_table);
+    let disassemble = arguments.get(1).is_some_and(|arg| arg == DISASSEMBLE_FLAG);
+    let rest = &arguments[if disassemble { 2 } else { 1 }..];
 
+    let (source_paths, config_args) = get_source_paths(rest);
+
+    precompilation::argumentprocessor::run(
+        config_args,
+        sysdefaults::DIRECTIVE_PREFIX,
+        sysdefaults::DELIMITER,
+        &mut config_table
+    );
 
-    let bits = (machine_operation_bits, addressing_mode_bits, operand_bits, machine_operation_bits+addressing_mode_bits+2*operand_bits);
+    if disassemble {
+
+        if source_paths.len() > 1 {
+
+            eprintln!("{} only disassembles a single file, linking is not supported", DISASSEMBLE_FLAG);
+            exit(sysdefaults::EXIT_CODE);
+        }
+
+        let (memory, bits) = assemble(&source_paths[0], &mut config_table);
+        let number_gprs = config_table[sysdefaults::REGISTERS_CONFIG.0];
+
+        print!("{}", disassembler::disassemble(&memory, &bits, number_gprs, 0..memory.highest_address()+1));
+        return;
+    }
+
+    let (mut memory, bits) = match source_paths {
+
+        [source_path] => assemble(source_path, &mut config_table),
+        _ => link_relocatable(source_paths, &config_table)
+    };
 
     virtualmachine::run(&config_table, &mut memory, &bits);
 }
@@ -1,6 +1,7 @@
 pub mod codegenerator;
 pub mod instructionpools;
 pub mod lexer;
+pub mod linker;
 pub mod parser;
 pub mod precompilation;
 pub mod semanticanalyser;
@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use crate::csm::structs::{Scope, Symbol, Operand, Instruction, Memory};
+use crate::csm::structs::{Scope, Symbol, Operand, Instruction, Memory, Relocation, Rebase, RelocatableObject};
 use crate::csm::defaults::{sysdefaults, SymbolTypes};
 use crate::csm::tokens::{TokenTypes, TypedToken};
 use crate::csm::binarystring;
@@ -59,6 +59,27 @@ fn resolve_operand(operand: &Operand, scope_symbol_table: &HashMap<&String, Symb
     }
 }
 
+/// Resolve an operand the same way `resolve_operand` does, except a label this object does not itself define
+/// (i.e. absent from both symbol tables) resolves to `None` instead of panicking, so the caller can record a
+/// relocation and fall back to a placeholder
+/// The `bool` alongside a resolved value marks whether it is an address within this object's own `words` (a
+/// label the object defines itself) - such a value is only correct relative to this object starting at 0, so
+/// the caller must record a `Rebase` for it, the same way an unresolved label gets a `Relocation`
+fn resolve_operand_relocatable(operand: &Operand, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>, number_gprs: usize) -> Option<(isize, bool)> {
+
+    match operand.operand_value.token_type {
+
+        TokenTypes::Label => {
+
+            scope_symbol_table.get(&operand.operand_value.token_value)
+                .or_else(|| global_symbol_table.get(&operand.operand_value.token_value))
+                .map(|symbol| (symbol.symbol_value, true))
+        },
+
+        _ => Some((resolve_operand(operand, scope_symbol_table, global_symbol_table, number_gprs), false))
+    }
+}
+
 /// Generate the machine code (bits) that would represent a low level CPU instruction
 fn generate_machine_operation(instruction: &Instruction, source_operand: &Operand, destination_operand: &Operand,
                               scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>, number_gprs: usize,
@@ -75,6 +96,59 @@ fn generate_machine_operation(instruction: &Instruction, source_operand: &Operan
     [instruction_binary, addressing_mode_binary, source_operand_binary, destination_operand_binary].concat()
 }
 
+/// Generate a relocatable machine word: identical to `generate_machine_operation`, except an operand referencing
+/// a symbol outside this object is encoded as a zeroed placeholder and recorded in `relocations` for a linker
+/// to patch in later, instead of panicking against an incomplete symbol table, and an operand already resolved
+/// to an address this object itself defines is recorded in `rebases`, since that address is only correct
+/// relative to this object's own `words` until a linker shifts it by the object's load offset
+fn generate_machine_operation_relocatable(word_index: usize, instruction: &Instruction, source_operand: &Operand, destination_operand: &Operand,
+                                          scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>, number_gprs: usize,
+                                          machine_operation_bits: usize, addressing_mode_bits: usize, operand_bits: usize,
+                                          relocations: &mut Vec<Relocation>, rebases: &mut Vec<Rebase>) -> String {
+
+    let instruction_binary = binarystring::unsigned_int(instruction.opcode as isize, machine_operation_bits as isize);
+
+    let addressing_mode_binary = binarystring::unsigned_int(addressingmodes::ADDRESSING_MODES[&source_operand.addressing_mode.token_value].opcode as isize, addressing_mode_bits as isize);
+
+    let source_operand_binary = match resolve_operand_relocatable(source_operand, scope_symbol_table, global_symbol_table, number_gprs) {
+
+        Some((value, needs_rebase)) => {
+
+            if needs_rebase {
+
+                rebases.push(Rebase { word_index, is_source: true });
+            }
+
+            binarystring::signed_int(value, operand_bits as isize)
+        },
+        None => {
+
+            relocations.push(Relocation { word_index, is_source: true, symbol: source_operand.operand_value.token_value.to_owned() });
+            binarystring::signed_int(0, operand_bits as isize)
+        }
+    };
+
+    let destination_operand_binary = match resolve_operand_relocatable(destination_operand, scope_symbol_table, global_symbol_table, number_gprs) {
+
+        Some((value, needs_rebase)) => {
+
+            if needs_rebase {
+
+                rebases.push(Rebase { word_index, is_source: false });
+            }
+
+            binarystring::signed_int(value, operand_bits as isize)
+        },
+        None => {
+
+            relocations.push(Relocation { word_index, is_source: false, symbol: destination_operand.operand_value.token_value.to_owned() });
+            binarystring::signed_int(0, operand_bits as isize)
+        }
+    };
+
+    [instruction_binary, addressing_mode_binary, source_operand_binary, destination_operand_binary].concat()
+}
+
 /// Update any local symbols and prematurely place any variables into the memory pool
 fn update_local_symbols(index: &mut usize, offset: &mut usize, scope: &mut Scope, memory: &mut Memory, total_bits: usize) {
 
@@ -92,15 +166,97 @@ fn update_local_symbols(index: &mut usize, offset: &mut usize, scope: &mut Scope
         } else if symbol.symbol_type == SymbolTypes::Variable {
 
             //  Place variables at the end of the instructions
-            memory.insert_binary(*offset as isize, binarystring::signed_int(symbol.symbol_value, total_bits as isize));
+            // The code generator only ever computes addresses within the allocated memory pool
+            memory.insert_binary(*offset as isize, binarystring::signed_int(symbol.symbol_value, total_bits as isize))
+                  .expect("code generator computed a variable address outside of memory");
             symbol.symbol_value = *offset as isize;
             *offset += 1;
         }
     }
 }
 
+/// Update any local symbols and prematurely place any variables into a relocatable object's `words`
+/// Identical to `update_local_symbols`, except variables are never external (their value is always a literal),
+/// so no placeholder/relocation bookkeeping is needed here
+fn update_local_symbols_relocatable(index: &mut usize, offset: &mut usize, scope: &mut Scope, words: &mut Vec<String>, total_bits: usize) {
+
+    *offset += scope.num_instructions as usize;
+
+    for symbol in scope.symbol_table.values_mut() {
+
+        if symbol.symbol_type == SymbolTypes::Branch {
+
+            symbol.symbol_value += *index as isize
+
+        } else if symbol.symbol_type == SymbolTypes::Variable {
+
+            words[*offset] = binarystring::signed_int(symbol.symbol_value, total_bits as isize);
+            symbol.symbol_value = *offset as isize;
+            *offset += 1;
+        }
+    }
+}
+
+/// Generate the relocatable code for a given scope, recording a relocation in place of any external reference
+fn generate_code_relocatable(index: &mut usize, offset: &mut usize, scope_tokens: &Vec<&TypedToken>, scope_symbol_table: &HashMap<&String, Symbol>,
+                             global_symbol_table: &HashMap<&String, Symbol>, words: &mut Vec<String>,
+                             number_gprs: usize, machine_operation_bits: usize, addressing_mode_bits: usize, operand_bits: usize,
+                             relocations: &mut Vec<Relocation>, rebases: &mut Vec<Rebase>) {
+
+    let (mut current_source, mut current_destination);
+    let default_operand = Operand {
+
+        addressing_mode: &TypedToken {
+            token_type: TokenTypes::AddressingMode,
+            token_value: addressingmodes::REGISTER.symbol.to_owned(),
+            row: 0,
+            column: 0
+        },
+
+        operand_value: &TypedToken {
+            token_type: TokenTypes::Value,
+            token_value: sysdefaults::OPERAND_VALUE.to_owned(),
+            row: 0,
+            column: 0
+        },
+    };
+
+    for (i, token) in scope_tokens.iter().enumerate() {
+
+        if token.token_type == TokenTypes::Instruction {
+
+            let instruction = instructions::INSTRUCTION_SET[&token.token_value];
+
+            let source_operand = if instruction.operands > 0 {
+
+                current_source = Operand{ addressing_mode: scope_tokens[i+1], operand_value: scope_tokens[i+2] };
+                &current_source
+
+            } else {
+
+                &default_operand
+            };
+
+            let destination_operand = if instruction.operands > 1 {
+
+                current_destination = Operand{ addressing_mode: scope_tokens[i+4], operand_value: scope_tokens[i+5] };
+                &current_destination
+
+            } else {
+
+                &default_operand
+            };
+
+            words[*index] = generate_machine_operation_relocatable(*index, &instruction, source_operand, destination_operand, scope_symbol_table, global_symbol_table, number_gprs, machine_operation_bits, addressing_mode_bits, operand_bits, relocations, rebases);
+            *index += 1;
+        }
+    }
+
+    *index = *offset;
+}
+
 /// Generate the code for a given scope
-fn generate_code(index: &mut usize, offset: &mut usize, scope_tokens: &Vec<&TypedToken>, scope_symbol_table: &HashMap<&String, Symbol>, 
+fn generate_code(index: &mut usize, offset: &mut usize, scope_tokens: &Vec<&TypedToken>, scope_symbol_table: &HashMap<&String, Symbol>,
                  global_symbol_table: &HashMap<&String, Symbol>, memory: &mut Memory,
                  number_gprs: usize, machine_operation_bits: usize, addressing_mode_bits: usize, operand_bits: usize) {
 
@@ -149,7 +305,9 @@ fn generate_code(index: &mut usize, offset: &mut usize, scope_tokens: &Vec<&Type
                 &default_operand
             };
 
-            memory.insert_binary(*index as isize, generate_machine_operation(&instruction, source_operand, destination_operand, scope_symbol_table, global_symbol_table, number_gprs, machine_operation_bits, addressing_mode_bits, operand_bits));
+            // The code generator only ever computes addresses within the allocated memory pool
+            memory.insert_binary(*index as isize, generate_machine_operation(&instruction, source_operand, destination_operand, scope_symbol_table, global_symbol_table, number_gprs, machine_operation_bits, addressing_mode_bits, operand_bits))
+                  .expect("code generator computed an instruction address outside of memory");
             *index += 1;
         }
     }
@@ -167,11 +325,7 @@ pub fn run<'a>(global_scope: &mut Scope<'a>, procedure_scopes: &mut HashMap<&'a
     let number_gprs = config_table[sysdefaults::REGISTERS_CONFIG.0];
     let number_registers = number_gprs + registers::NUMBER_SP_REGISTERS;
 
-    let number_memory_addresses = config_table[sysdefaults::MEMORY_CONFIG.0] as usize;
-
-    let operand_bits = if number_registers > number_memory_addresses
-                            { binarystring::number_bits(number_registers) }
-                            else { binarystring::number_bits(number_memory_addresses) } + 1;
+    let operand_bits = sysdefaults::operand_bits(config_table);
 
     let total_bits = machine_operation_bits + addressing_mode_bits + 2*operand_bits;
 
@@ -192,3 +346,47 @@ pub fn run<'a>(global_scope: &mut Scope<'a>, procedure_scopes: &mut HashMap<&'a
 
     (memory, machine_operation_bits, addressing_mode_bits, operand_bits)
 }
+
+/// Run the CodeGenerator in relocatable mode, for a file that is meant to be linked with others rather than
+/// run on its own
+/// Mirrors `run`: the same global/local symbol bookkeeping and bit-width sizing, except the result is a
+/// `RelocatableObject` sized to exactly this file's own instructions and variables (no load address assumed yet),
+/// and references to labels the file never declares become placeholders plus a `Relocation` instead of a panic
+pub fn run_relocatable<'a>(global_scope: &mut Scope<'a>, procedure_scopes: &mut HashMap<&'a String, Scope>, config_table: &HashMap<String, usize>) -> (RelocatableObject, usize, usize, usize) {
+
+    let machine_operation_bits = binarystring::number_bits(instructions::NUMBER_INSTRUCTIONS - 1);
+    let addressing_mode_bits = binarystring::number_bits(addressingmodes::NUMBER_MODES - 1);
+
+    let number_gprs = config_table[sysdefaults::REGISTERS_CONFIG.0];
+    let number_registers = number_gprs + registers::NUMBER_SP_REGISTERS;
+
+    let operand_bits = sysdefaults::operand_bits(config_table);
+
+    let total_bits = machine_operation_bits + addressing_mode_bits + 2*operand_bits;
+
+    let (mut index, mut offset) = (0, 0);
+
+    let word_count = global_scope.num_instructions + global_scope.num_variables
+        + procedure_scopes.values().map(|scope| scope.num_instructions + scope.num_variables).sum::<usize>();
+
+    let mut words = vec!["0".repeat(total_bits); word_count];
+    let mut relocations = Vec::new();
+    let mut rebases = Vec::new();
+
+    update_global_symbols(global_scope, procedure_scopes);
+
+    update_local_symbols_relocatable(&mut index, &mut offset, global_scope, &mut words, total_bits);
+    generate_code_relocatable(&mut index, &mut offset, &global_scope.tokens, &global_scope.symbol_table, &global_scope.symbol_table, &mut words, number_gprs, machine_operation_bits, addressing_mode_bits, operand_bits, &mut relocations, &mut rebases);
+
+    for scope in procedure_scopes.values_mut() {
+
+        update_local_symbols_relocatable(&mut index, &mut offset, scope, &mut words, total_bits);
+        generate_code_relocatable(&mut index, &mut offset, &scope.tokens, &scope.symbol_table, &global_scope.symbol_table, &mut words, number_gprs, machine_operation_bits, addressing_mode_bits, operand_bits, &mut relocations, &mut rebases)
+    }
+
+    let exports = global_scope.symbol_table.iter()
+        .map(|(name, symbol)| (name.to_string(), symbol.symbol_value))
+        .collect();
+
+    (RelocatableObject { words, exports, relocations, rebases }, machine_operation_bits, addressing_mode_bits, operand_bits)
+}
@@ -4,6 +4,7 @@ use crate::csm::structs::{Scope, Symbol};
 use crate::csm::tokens::{TypedToken, TokenTypes};
 use crate::csm::errors::{Errors, errormessages};
 use crate::csm::defaults::{sysdefaults, SymbolTypes};
+use crate::csm::sourcemap::SourceMap;
 
 /// Accumulate all tokens encountered in the scope
 fn get_scope<'a>(tokens: &'a Vec<TypedToken>, index: &mut usize) -> Vec<&'a TypedToken> {
@@ -72,8 +73,30 @@ fn update_global_scope<'a>(global_scope: &mut Scope<'a>, procedure_scopes: &mut
     }
 }
 
+/// Return the source line at the given 1-indexed row, or an empty string if it falls outside the source
+fn source_line(source_code: &str, row: usize) -> &str {
+
+    source_code.lines().nth(row.saturating_sub(1)).unwrap_or("")
+}
+
+/// Resolve a merged-document row back to the row the user actually wrote it at, annotating `message` with the
+/// originating file when `include` has spliced in another file's tokens
+fn locate(source_map: &SourceMap, main_path: &str, row: usize, message: impl Into<String>) -> (usize, String) {
+
+    let (resolved_row, file) = source_map.locate(main_path, row);
+
+    match file {
+
+        Some(file) => (resolved_row, format!("{} (in {file})", message.into())),
+        None => (resolved_row, message.into())
+    }
+}
+
 /// Verify the validity of a symbol
-fn handle_symbol(symbol: &Symbol, current_token: &TypedToken, next_token: &TypedToken, errors: &mut Errors) {
+fn handle_symbol(symbol: &Symbol, current_token: &TypedToken, next_token: &TypedToken, source_code: &str, source_map: &SourceMap, main_path: &str, errors: &mut Errors) {
+
+    let line = source_line(source_code, current_token.row);
+    let span = current_token.token_value.len();
 
     // Branch Label
     if next_token.token_type == TokenTypes::Instruction {
@@ -82,17 +105,20 @@ fn handle_symbol(symbol: &Symbol, current_token: &TypedToken, next_token: &Typed
 
             SymbolTypes::Procedure => {
 
-                errors.record_error(current_token.row, current_token.column, errormessages::PROC_TO_BRANCH_REDECL.error_type, errormessages::PROC_TO_BRANCH_REDECL.error_message);
+                let (row, message) = locate(source_map, main_path, current_token.row, errormessages::PROC_TO_BRANCH_REDECL.error_message);
+                errors.record_error_spanned(row, current_token.column, errormessages::PROC_TO_BRANCH_REDECL.error_type, message, line, span);
             },
 
             SymbolTypes::Branch => {
 
-                errors.record_error(current_token.row, current_token.column, errormessages::DUPLICATE_BRANCH.error_type, errormessages::DUPLICATE_BRANCH.error_message);
+                let (row, message) = locate(source_map, main_path, current_token.row, errormessages::DUPLICATE_BRANCH.error_message);
+                errors.record_error_spanned(row, current_token.column, errormessages::DUPLICATE_BRANCH.error_type, message, line, span);
             },
-            
+
             SymbolTypes::Variable => {
 
-                errors.record_error(current_token.row, current_token.column, errormessages::VAR_TO_BRANCH_REDECL.error_type, errormessages::VAR_TO_BRANCH_REDECL.error_message);
+                let (row, message) = locate(source_map, main_path, current_token.row, errormessages::VAR_TO_BRANCH_REDECL.error_message);
+                errors.record_error_spanned(row, current_token.column, errormessages::VAR_TO_BRANCH_REDECL.error_type, message, line, span);
             }
         }
 
@@ -103,17 +129,20 @@ fn handle_symbol(symbol: &Symbol, current_token: &TypedToken, next_token: &Typed
 
             SymbolTypes::Procedure => {
 
-                errors.record_error(current_token.row, current_token.column, errormessages::PROC_TO_VAR_REDECL.error_type, errormessages::PROC_TO_VAR_REDECL.error_message);
+                let (row, message) = locate(source_map, main_path, current_token.row, errormessages::PROC_TO_VAR_REDECL.error_message);
+                errors.record_error_spanned(row, current_token.column, errormessages::PROC_TO_VAR_REDECL.error_type, message, line, span);
             },
 
             SymbolTypes::Branch => {
 
-                errors.record_error(current_token.row, current_token.column, errormessages::BRACH_TO_VAR_REDECL.error_type, errormessages::PROC_TO_BRANCH_REDECL.error_message);
+                let (row, message) = locate(source_map, main_path, current_token.row, errormessages::BRACH_TO_VAR_REDECL.error_message);
+                errors.record_error_spanned(row, current_token.column, errormessages::BRACH_TO_VAR_REDECL.error_type, message, line, span);
             },
-            
+
             SymbolTypes::Variable => {
 
-                errors.record_error(current_token.row, current_token.column, errormessages::DUPLICATE_VAR.error_type, errormessages::DUPLICATE_VAR.error_message);
+                let (row, message) = locate(source_map, main_path, current_token.row, errormessages::DUPLICATE_VAR.error_message);
+                errors.record_error_spanned(row, current_token.column, errormessages::DUPLICATE_VAR.error_type, message, line, span);
             }
         }
     }
@@ -131,7 +160,7 @@ fn remove_variable(scope: &mut Scope, index: usize) {
 }
 
 /// Verify and update the symbol table accordingly for a given label
-fn handle_label(scope: &mut Scope, index: usize, statements: usize, errors: &mut Errors) {
+fn handle_label(scope: &mut Scope, index: usize, statements: usize, source_code: &str, source_map: &SourceMap, main_path: &str, errors: &mut Errors) {
 
     let first_token = scope.tokens[index]; // The label token
     let second_token = scope.tokens[index+1]; // Either a directive, instruction or end token
@@ -141,7 +170,7 @@ fn handle_label(scope: &mut Scope, index: usize, statements: usize, errors: &mut
 
         let symbol = &scope.symbol_table[&first_token.token_value];
 
-        handle_symbol(symbol, first_token, second_token, errors);
+        handle_symbol(symbol, first_token, second_token, source_code, source_map, main_path, errors);
     
     } else {
 
@@ -172,7 +201,7 @@ fn handle_label(scope: &mut Scope, index: usize, statements: usize, errors: &mut
 }
 
 /// Update the symbol table with its labels for a given scope
-fn update_symbol_table(scope: &mut Scope, errors: &mut Errors) {
+fn update_symbol_table(scope: &mut Scope, source_code: &str, source_map: &SourceMap, main_path: &str, errors: &mut Errors) {
 
     let (mut index, mut statements) = (0, 0);
 
@@ -182,8 +211,8 @@ fn update_symbol_table(scope: &mut Scope, errors: &mut Errors) {
         if scope.tokens[index].token_type == TokenTypes::Label &&
            matches!(scope.tokens[index+1].token_type, TokenTypes::Instruction | TokenTypes::AssemblyDirective) {
 
-            handle_label(scope, index, statements, errors)
-        
+            handle_label(scope, index, statements, source_code, source_map, main_path, errors)
+
         } else if scope.tokens[index].token_type == TokenTypes::Instruction {
 
             statements += 1;
@@ -197,7 +226,7 @@ fn update_symbol_table(scope: &mut Scope, errors: &mut Errors) {
 
 /// Run the InstructionPools class
 /// Will return the global and procedure scopes
-pub fn run(tokens: &Vec<TypedToken>) -> (Scope, HashMap<&String, Scope>) {
+pub fn run<'a>(tokens: &'a Vec<TypedToken>, source_code: &'a str, main_path: &str, source_map: &SourceMap) -> (Scope<'a>, HashMap<&'a String, Scope<'a>>) {
 
     let (mut index, mut errors) = (0, Errors::new());
 
@@ -214,14 +243,17 @@ pub fn run(tokens: &Vec<TypedToken>) -> (Scope, HashMap<&String, Scope>) {
 
     update_global_scope(&mut global_scope, &mut procedure_scopes);
 
-    update_symbol_table(&mut global_scope, &mut errors);
+    update_symbol_table(&mut global_scope, source_code, source_map, main_path, &mut errors);
 
     for scope in procedure_scopes.values_mut() {
 
-        update_symbol_table(scope, &mut errors);
+        update_symbol_table(scope, source_code, source_map, main_path, &mut errors);
+    }
+
+    if errors.get_errors(sysdefaults::INSTRUCTION_POOL_ERRORS_HEADER) > 0 {
+
+        std::process::exit(sysdefaults::EXIT_CODE);
     }
 
-    errors.get_errors(sysdefaults::INSTRUCTION_POOL_ERRORS_HEADER);
-    
     (global_scope, procedure_scopes)
 }
@@ -281,7 +281,10 @@ pub fn run(source_code: &str, directive_prefix: char, comment_prefix: char) -> V
 
     let tokens = tokenise(source_code, &mut index, directive_prefix, comment_prefix, &mut position, &mut errors);
 
-    errors.get_errors(sysdefaults::LEXER_ERRORS_HEADER);
+    if errors.get_errors(sysdefaults::LEXER_ERRORS_HEADER) > 0 {
+
+        std::process::exit(sysdefaults::EXIT_CODE);
+    }
 
     tokens
 }
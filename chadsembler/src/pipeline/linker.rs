@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use crate::csm::structs::{Memory, RelocatableObject};
+use crate::csm::binarystring;
+
+/// Patch a single operand field of an already-encoded word with a resolved value
+/// `bits` is `(machine_operation_bits, addressing_mode_bits, operand_bits)`; the source operand field
+/// immediately follows the opcode and addressing mode, the destination operand field follows that
+fn patch_operand(word: &str, bits: (usize, usize, usize), is_source: bool, value: isize) -> String {
+
+    let (machine_operation_bits, addressing_mode_bits, operand_bits) = bits;
+    let prefix_bits = machine_operation_bits + addressing_mode_bits + if is_source { 0 } else { operand_bits };
+
+    let resolved = binarystring::signed_int(value, operand_bits as isize);
+
+    format!("{}{}{}", &word[..prefix_bits], resolved, &word[prefix_bits+operand_bits..])
+}
+
+/// Shift an operand field that already holds an address within its own object's `words` by that object's
+/// load offset, so a label the object defines still points at the right place once the object sits somewhere
+/// other than address 0 in the linked image
+fn rebase_operand(word: &str, bits: (usize, usize, usize), is_source: bool, load_offset: isize) -> String {
+
+    let (machine_operation_bits, addressing_mode_bits, operand_bits) = bits;
+    let prefix_bits = machine_operation_bits + addressing_mode_bits + if is_source { 0 } else { operand_bits };
+
+    let value = binarystring::read_signed_int(&word[prefix_bits..prefix_bits+operand_bits])
+        .expect("code generator emitted a malformed operand field");
+
+    patch_operand(word, bits, is_source, value + load_offset)
+}
+
+/// Link several relocatable objects into a single flat `Memory` image, in argument order
+/// Concatenates every object's words one after another, rebasing each object's exported symbols by its load
+/// offset (the running word count so far) - the same bookkeeping `update_global_symbols` does for procedures
+/// within a single object, just generalised across whole objects - then resolves every recorded relocation
+/// against the combined export table and patches the placeholder it left behind, and rebases every operand
+/// that already resolved to an address within the object's own `words` (a label the object defines itself,
+/// whether referenced at global or procedure scope) by the same load offset
+pub fn link(objects: Vec<RelocatableObject>, bits: (usize, usize, usize), number_registers: usize) -> Memory {
+
+    let (machine_operation_bits, addressing_mode_bits, operand_bits) = bits;
+    let total_bits = machine_operation_bits + addressing_mode_bits + 2*operand_bits;
+
+    let mut load_offsets = Vec::with_capacity(objects.len());
+    let mut exports: HashMap<String, isize> = HashMap::new();
+    let mut offset = 0isize;
+
+    for object in &objects {
+
+        load_offsets.push(offset);
+
+        for (symbol, value) in &object.exports {
+
+            exports.insert(symbol.to_owned(), value + offset);
+        }
+
+        offset += object.words.len() as isize;
+    }
+
+    let mut memory = Memory::new(number_registers, total_bits, operand_bits);
+
+    for (object, load_offset) in objects.into_iter().zip(load_offsets) {
+
+        let mut words = object.words;
+
+        for relocation in &object.relocations {
+
+            let value = exports[&relocation.symbol];
+            words[relocation.word_index] = patch_operand(&words[relocation.word_index], bits, relocation.is_source, value);
+        }
+
+        for rebase in &object.rebases {
+
+            words[rebase.word_index] = rebase_operand(&words[rebase.word_index], bits, rebase.is_source, load_offset);
+        }
+
+        for (index, word) in words.into_iter().enumerate() {
+
+            memory.insert_binary(load_offset + index as isize, word)
+                  .expect("linker computed an address outside of memory");
+        }
+    }
+
+    memory
+}
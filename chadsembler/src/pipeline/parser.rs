@@ -1,6 +1,8 @@
 use std::process;
 use crate::csm::tokens::{TypedToken, TokenTypes};
 use crate::csm::defaults::sysdefaults;
+use crate::csm::errors::{Errors, Span, errormessages};
+use crate::csm::sourcemap::SourceMap;
 
 // Specify what Tokens can be found after certain Token Types
 const END: [TokenTypes; 5] = [TokenTypes::End, TokenTypes::Instruction, TokenTypes::Label, TokenTypes::RightBrace, TokenTypes::LeftBrace];
@@ -12,51 +14,68 @@ const SEPARATOR: [TokenTypes; 4] = [TokenTypes::AddressingMode, TokenTypes::Valu
 const SCOPE: [TokenTypes; 1] = [TokenTypes::End];
 const ASSEMBLY_DIRECTIVE: [TokenTypes; 2] = [TokenTypes::End, TokenTypes::Value];
 
-/// Append a tuple containing the syntactically invalid tokens
-fn record_error<'a>(first_token: &'a TypedToken, second_token: &'a TypedToken, errors: &mut Vec<(&'a TypedToken, &'a TypedToken)>) {
+/// Return the source line at the given 1-indexed row, or an empty string if it falls outside the source
+fn source_line(source_code: &str, row: usize) -> &str {
 
-    errors.push( (first_token, second_token) );
+    source_code.lines().nth(row.saturating_sub(1)).unwrap_or("")
 }
 
-/// If there are errors, will output all errors and exit the program
-fn get_errors(errors: &Vec<(&TypedToken, &TypedToken)>) {
+/// Resolve a merged-document row back to the row the user actually wrote it at, annotating `message` with the
+/// originating file when `include` has spliced in another file's tokens
+fn locate(source_map: &SourceMap, main_path: &str, row: usize, message: impl Into<String>) -> (usize, String) {
 
-    if !errors.is_empty() {
+    let (resolved_row, file) = source_map.locate(main_path, row);
 
-        eprintln!("{}", sysdefaults::PARSER_ERRORS_HEADER);
+    match file {
 
-        for (first, second) in errors {
+        Some(file) => (resolved_row, format!("{} (in {file})", message.into())),
+        None => (resolved_row, message.into())
+    }
+}
 
-            match (&first.token_type, &second.token_type) {
+/// Record a syntax error for `token` having followed `previous` illegally
+/// Matching braces report as an unclosed scope; an unexpected token in the middle of a statement reports both
+/// the previous token's span and the unexpected token's span together, so the report shows what preceded the
+/// mistake as well as where it landed
+fn record_unexpected(previous: &TypedToken, token: &TypedToken, source_code: &str, source_map: &SourceMap, main_path: &str, errors: &mut Errors) {
 
-                (TokenTypes::LeftBrace, TokenTypes::LeftBrace) => {
+    let line = source_line(source_code, token.row);
+    let span = token.token_value.len();
 
-                    eprintln!("Invalid Syntax Error {}:{} -> Block scope was opened but never closed", first.row, first.column);
-                },
+    let previous_line = source_line(source_code, previous.row);
+    let previous_span = previous.token_value.len();
 
-                (TokenTypes::RightBrace, TokenTypes::RightBrace) => {
+    match (&previous.token_type, &token.token_type) {
 
-                    eprintln!("Invalid Syntax Error {}:{} -> Block scope was opened but never closed", first.row, first.column);
-                },
+        (TokenTypes::LeftBrace, TokenTypes::LeftBrace) | (TokenTypes::RightBrace, TokenTypes::RightBrace) => {
 
-                (TokenTypes::End, _) => {
+            let (row, message) = locate(source_map, main_path, previous.row, "Block scope was opened but never closed");
+            errors.record_error_spanned(row, previous.column, errormessages::errortypes::SYNTAX, message, previous_line, previous_span);
+        },
 
-                    eprintln!("Invalid Syntax Error {}:{} -> Statement cannot begin with a {}", second.row, second.column, TokenTypes::type_to_str(&second.token_type));
-                },
+        (TokenTypes::End, _) => {
 
-                (_, TokenTypes::End) => {
+            let (row, message) = locate(source_map, main_path, token.row, format!("Statement cannot begin with a {}", TokenTypes::type_to_str(&token.token_type)));
+            errors.record_error_spanned(row, token.column, errormessages::errortypes::SYNTAX, message, line, span);
+        },
 
-                    eprintln!("Invalid Syntax Error {}:{} -> Statement cannot end with a {}", first.row, first.column, TokenTypes::type_to_str(&first.token_type));
-                },
+        (_, TokenTypes::End) => {
 
-                _ => {
+            let (row, message) = locate(source_map, main_path, previous.row, format!("Statement cannot end with a {}", TokenTypes::type_to_str(&previous.token_type)));
+            errors.record_error_spanned(row, previous.column, errormessages::errortypes::SYNTAX, message, previous_line, previous_span);
+        },
 
-                    eprintln!("Invalid Syntax Error {}:{} -> {} was found after {}", second.row, second.column, TokenTypes::type_to_str(&second.token_type), TokenTypes::type_to_str(&first.token_type));
-                }
-            }
-        }
+        _ => {
 
-        process::exit(sysdefaults::EXIT_CODE);
+            let (row, message) = locate(source_map, main_path, token.row, format!("{} found after {}", TokenTypes::type_to_str(&token.token_type), TokenTypes::type_to_str(&previous.token_type)));
+            let (previous_row, _) = source_map.locate(main_path, previous.row);
+
+            errors.record_error_related(
+                row, token.column, errormessages::errortypes::SYNTAX, message,
+                line, span,
+                previous_row, previous.column, "previous token", previous_line, previous_span
+            );
+        }
     }
 }
 
@@ -77,25 +96,25 @@ fn possible_tokens(token: &TypedToken) -> &[TokenTypes] {
 }
 
 /// Verify a block scope is valid
-fn validate_scope<'a>(token: &'a TypedToken, previous_scope: &mut Option<&'a TypedToken>, errors: &mut Vec<(&'a TypedToken, &'a TypedToken)>) {
+fn validate_scope<'a>(token: &'a TypedToken, previous_scope: &mut Option<&'a TypedToken>, source_code: &str, source_map: &SourceMap, main_path: &str, errors: &mut Errors) {
 
     if token.token_type == TokenTypes::LeftBrace {
 
         if previous_scope.is_none() {
 
             *previous_scope = Some(token);
-        
+
         } else {
 
-            record_error(token, token, errors);
+            record_unexpected(token, token, source_code, source_map, main_path, errors);
         }
 
     } else {
 
         if previous_scope.is_none() {
 
-            record_error(token, token, errors);
-        
+            record_unexpected(token, token, source_code, source_map, main_path, errors);
+
         } else {
 
             *previous_scope = None;
@@ -104,7 +123,7 @@ fn validate_scope<'a>(token: &'a TypedToken, previous_scope: &mut Option<&'a Typ
 }
 
 /// Verify the tokens match the syntax of the language
-fn parse<'a>(tokens: &'a Vec<TypedToken>, previous_scope: &mut Option<&'a TypedToken>, errors: &mut Vec<(&'a TypedToken, &'a TypedToken)>) {
+fn parse<'a>(tokens: &'a Vec<TypedToken>, previous_scope: &mut Option<&'a TypedToken>, source_code: &str, source_map: &SourceMap, main_path: &str, errors: &mut Errors) {
 
     // Last token is always an END Token
     // Initialise with END token to open the start of a new statement
@@ -115,29 +134,37 @@ fn parse<'a>(tokens: &'a Vec<TypedToken>, previous_scope: &mut Option<&'a TypedT
         if token.token_type == TokenTypes::LeftBrace ||
            token.token_type == TokenTypes::RightBrace {
 
-            validate_scope(token, previous_scope, errors)
+            validate_scope(token, previous_scope, source_code, source_map, main_path, errors)
         }
 
         if !possible_tokens(previous).contains(&token.token_type) {
 
-            record_error(previous, token, errors);
+            record_unexpected(previous, token, source_code, source_map, main_path, errors);
         }
 
         previous = token;
     }
 
-    // Ensure there is no unclosed block scope
+    // Ensure there is no unclosed block scope, pointing back at the opening `{` rather than wherever parsing ended
     if let Some(left_brace) = previous_scope {
 
-        record_error(left_brace, left_brace, errors);
+        let line = source_line(source_code, left_brace.row);
+        let span = left_brace.token_value.len();
+        let (row, message) = locate(source_map, main_path, left_brace.row, "Block scope was opened but never closed");
+
+        errors.record_error_noted(row, left_brace.column, errormessages::errortypes::SYNTAX, message, Span { source_line: line, span }, "this `{` has no matching `}`");
     }
 }
 
 /// Run the Parser
-pub fn run(tokens: &Vec<TypedToken>) {
+pub fn run(tokens: &Vec<TypedToken>, source_code: &str, main_path: &str, source_map: &SourceMap) {
+
+    let (mut previous_scope, mut errors) = (None, Errors::new());
 
-    let (mut previous_scope, mut errors) = (None, Vec::new());
+    parse(tokens, &mut previous_scope, source_code, source_map, main_path, &mut errors);
 
-    parse(tokens, &mut previous_scope, &mut errors);
-    get_errors(&errors);
+    if errors.get_errors(sysdefaults::PARSER_ERRORS_HEADER) > 0 {
+
+        process::exit(sysdefaults::EXIT_CODE);
+    }
 }
\ No newline at end of file
@@ -24,7 +24,235 @@ pub mod argumentprocessor {
             position.column = 1;
         }
 
-        errors.get_errors(sysdefaults::ARGUMENT_PROCESSOR_ERRORS_HEADER)
+        if errors.get_errors(sysdefaults::ARGUMENT_PROCESSOR_ERRORS_HEADER) > 0 {
+
+            std::process::exit(sysdefaults::EXIT_CODE);
+        }
+    }
+}
+
+/// Splice `include`d files in and substitute `macro` constants, producing a single source string for the
+/// lexer to tokenise as though it had always been one file, plus a `SourceMap` recording which file and row
+/// each merged row actually came from
+/// Runs before the lexer: `include`/`macro` are plain words rather than `!`-prefixed directives, so they are
+/// expanded at the raw text level instead of needing a dedicated token type
+/// `include`/`macro` must be the only content on their line, just like every other directive in this assembler -
+/// that keeps the row bookkeeping a simple one-row-in/zero-or-more-rows-out substitution rather than something
+/// that has to track position character by character
+pub mod expander {
+
+    use super::*;
+    use std::collections::HashSet;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use crate::csm::errors::errormessages;
+    use crate::csm::sourcemap::SourceMap;
+
+    const INCLUDE_KEYWORD: &str = "INCLUDE";
+    const MACRO_KEYWORD: &str = "MACRO";
+    const END_KEYWORD: &str = "END";
+
+    /// Read the run of label characters starting at `index`, advancing `index` past it
+    fn read_word(source_code: &str, index: &mut usize) -> String {
+
+        let lower = *index;
+
+        while *index < source_code.len() && lexerdefaults::LABEL_CHARS.contains(source_code.as_bytes()[*index] as char) {
+
+            *index += 1;
+        }
+
+        source_code[lower..*index].to_owned()
+    }
+
+    /// Read a `"quoted"` path starting at the opening quote at `index`, advancing `index` past the closing quote
+    /// Returns `None` if there is no closing quote
+    fn read_quoted(source_code: &str, index: &mut usize) -> Option<String> {
+
+        *index += 1;
+        let lower = *index;
+
+        while *index < source_code.len() && source_code.as_bytes()[*index] as char != '"' {
+
+            *index += 1;
+        }
+
+        if *index >= source_code.len() {
+
+            return None;
+        }
+
+        let path = source_code[lower..*index].to_owned();
+        *index += 1;
+
+        Some(path)
+    }
+
+    /// Skip whitespace starting at `index`
+    fn skip_whitespace(source_code: &str, index: &mut usize) {
+
+        while *index < source_code.len() && lexerdefaults::WHITESPACE_CHARS.contains(source_code.as_bytes()[*index] as char) {
+
+            *index += 1;
+        }
+    }
+
+    /// Resolve an `include`d path relative to the directory of the file that references it
+    fn resolve_path(including_file: &Path, path: &str) -> PathBuf {
+
+        including_file.parent().map_or_else(|| PathBuf::from(path), |parent| parent.join(path))
+    }
+
+    /// Substitute macro-name words with their defined values across a single line
+    /// Stops at the first comment/directive prefix, leaving the rest of the line untouched, exactly like the
+    /// lexer itself treats everything from that point on as opaque
+    fn substitute_macros(line: &str, macros: &HashMap<String, String>, directive_prefix: char, comment_prefix: char) -> String {
+
+        let mut result = String::with_capacity(line.len());
+        let mut index = 0;
+
+        while index < line.len() {
+
+            let c = line.as_bytes()[index] as char;
+
+            if c == comment_prefix || c == directive_prefix {
+
+                result += &line[index..];
+                break;
+            }
+
+            if lexerdefaults::LABEL_CHARS.contains(c) {
+
+                let lower = index;
+                let word = sysdefaults::default_casing(&read_word(line, &mut index));
+
+                match macros.get(&word) {
+
+                    Some(value) => result += value,
+                    None => result += &line[lower..index]
+                }
+
+                continue;
+            }
+
+            result.push(c);
+            index += 1;
+        }
+
+        result
+    }
+
+    /// Recursively expand `include`/`macro` constructs in a single file's source text into a flat string, plus a
+    /// `SourceMap` recording which file and row each of that string's rows came from
+    /// `visited` detects include cycles via each file's canonicalised path
+    /// `macros` persists across includes, so a macro defined in one file is visible to files that include it afterwards
+    fn expand(source_code: &str, path: &Path, directive_prefix: char, comment_prefix: char,
+             visited: &mut HashSet<PathBuf>, macros: &mut HashMap<String, String>, errors: &mut Errors) -> (String, SourceMap) {
+
+        let mut expanded = String::with_capacity(source_code.len());
+        let mut source_map = SourceMap::new();
+
+        for (line_index, raw_line) in source_code.split_inclusive('\n').enumerate() {
+
+            let row = line_index + 1;
+            let line = raw_line.strip_suffix('\n').unwrap_or(raw_line);
+            let trimmed = line.trim_start();
+            let column = line.len() - trimmed.len() + 1;
+
+            let mut index = 0;
+
+            let keyword = if trimmed.as_bytes().first().is_some_and(|&b| lexerdefaults::LABEL_CHARS.contains(b as char))
+                { Some(sysdefaults::default_casing(&read_word(trimmed, &mut index))) } else { None };
+
+            if keyword.as_deref() == Some(INCLUDE_KEYWORD) {
+
+                skip_whitespace(trimmed, &mut index);
+
+                let included_path = if index < trimmed.len() && trimmed.as_bytes()[index] as char == '"'
+                    { read_quoted(trimmed, &mut index) } else { None };
+
+                match included_path {
+
+                    Some(included_path) => {
+
+                        let resolved = resolve_path(path, &included_path);
+                        let canonical = fs::canonicalize(&resolved).unwrap_or_else(|_| resolved.clone());
+
+                        if !visited.insert(canonical.clone()) {
+
+                            errors.record_error(row, column, errormessages::CIRCULAR_INCLUDE.error_type, errormessages::CIRCULAR_INCLUDE.error_message);
+
+                        } else {
+
+                            match fs::read_to_string(&resolved) {
+
+                                Ok(included_source) => {
+
+                                    let (included_text, included_map) = expand(&included_source, &resolved, directive_prefix, comment_prefix, visited, macros, errors);
+
+                                    expanded += &included_text;
+                                    source_map.extend(included_map);
+                                },
+                                Err(_) => errors.record_error(row, column, errormessages::INCLUDE_NOT_FOUND.error_type, errormessages::INCLUDE_NOT_FOUND.error_message)
+                            }
+
+                            visited.remove(&canonical);
+                        }
+                    },
+
+                    None => errors.record_error(row, column, errormessages::MALFORMED_INCLUDE.error_type, errormessages::MALFORMED_INCLUDE.error_message)
+                }
+
+            } else if keyword.as_deref() == Some(MACRO_KEYWORD) {
+
+                skip_whitespace(trimmed, &mut index);
+                let name = sysdefaults::default_casing(&read_word(trimmed, &mut index));
+
+                skip_whitespace(trimmed, &mut index);
+                let value = read_word(trimmed, &mut index);
+
+                skip_whitespace(trimmed, &mut index);
+                let terminator = sysdefaults::default_casing(&read_word(trimmed, &mut index));
+
+                if name.is_empty() || value.is_empty() || terminator != END_KEYWORD {
+
+                    errors.record_error(row, column, errormessages::MALFORMED_MACRO.error_type, errormessages::MALFORMED_MACRO.error_message);
+
+                } else {
+
+                    macros.insert(name, value);
+                }
+
+                // A macro definition contributes no row of its own to the merged output
+
+            } else {
+
+                expanded += &substitute_macros(line, macros, directive_prefix, comment_prefix);
+                expanded.push('\n');
+                source_map.push(path, row);
+            }
+        }
+
+        (expanded, source_map)
+    }
+
+    /// Run the Expander
+    pub fn run(source_code: &str, path: &str, directive_prefix: char, comment_prefix: char) -> (String, SourceMap) {
+
+        let mut visited = HashSet::new();
+        visited.insert(fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path)));
+
+        let mut macros = HashMap::new();
+        let mut errors = Errors::new();
+
+        let (expanded, source_map) = expand(source_code, Path::new(path), directive_prefix, comment_prefix, &mut visited, &mut macros, &mut errors);
+
+        if errors.get_errors(sysdefaults::EXPANDER_ERRORS_HEADER) > 0 {
+
+            std::process::exit(sysdefaults::EXIT_CODE);
+        }
+
+        (expanded, source_map)
     }
 }
 
@@ -108,6 +336,9 @@ pub mod preprocessor {
             position.column += 1;
         }
 
-        errors.get_errors(sysdefaults::PREPROCESSOR_ERRORS_HEADER);
+        if errors.get_errors(sysdefaults::PREPROCESSOR_ERRORS_HEADER) > 0 {
+
+            std::process::exit(sysdefaults::EXIT_CODE);
+        }
     }
 }
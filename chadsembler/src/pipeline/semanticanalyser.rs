@@ -1,10 +1,54 @@
 use std::collections::HashMap;
 use crate::csm::structs::{Scope, Symbol, Operand};
 use crate::csm::tokens::{TypedToken, TokenTypes};
-use crate::csm::errors::{Errors, errormessages};
+use crate::csm::errors::{Errors, Span, errormessages};
+use crate::csm::defaults::SymbolTypes;
 use crate::csm::defaults::sysdefaults;
 use crate::csm::architecture::{instructions, addressingmodes};
 
+/// Return the source line at the given 1-indexed row, or an empty string if it falls outside the source
+fn source_line(source_code: &str, row: usize) -> &str {
+
+    source_code.lines().nth(row.saturating_sub(1)).unwrap_or("")
+}
+
+/// The Levenshtein edit distance between two strings, used to suggest the nearest in-scope symbol
+/// to an undeclared label
+fn edit_distance(left: &str, right: &str) -> usize {
+
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+
+    let mut previous_row: Vec<usize> = (0..=right.len()).collect();
+
+    for i in 1..=left.len() {
+
+        let mut current_row = vec![i; right.len() + 1];
+
+        for j in 1..=right.len() {
+
+            let substitution_cost = if left[i-1] == right[j-1] { 0 } else { 1 };
+
+            current_row[j] = std::cmp::min(
+                std::cmp::min(current_row[j-1] + 1, previous_row[j] + 1),
+                previous_row[j-1] + substitution_cost
+            );
+        }
+
+        previous_row = current_row;
+    }
+
+    previous_row[right.len()]
+}
+
+/// Find the closest-spelled symbol name to `label` across both symbol tables, for use as a diagnostic note
+fn nearest_symbol(label: &str, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>) -> Option<String> {
+
+    scope_symbol_table.keys().chain(global_symbol_table.keys())
+        .min_by_key(|symbol| edit_distance(label, symbol.as_str()))
+        .map(|symbol| symbol.to_string())
+}
+
 /// Return the number of operands found for an instruction in the token stream
 fn count_operands(mut index: usize, tokens: &Vec<&TypedToken>) -> isize {
 
@@ -24,44 +68,82 @@ fn count_operands(mut index: usize, tokens: &Vec<&TypedToken>) -> isize {
 }
 
 /// Verify the semantic validity of the addressing mode of an operand
-fn analyse_addressing_mode(operand: &Operand, errors: &mut Errors) {
+fn analyse_addressing_mode(operand: &Operand, source_code: &str, errors: &mut Errors) {
+
+    let span = operand.operand_value.token_value.len();
+    let line = source_line(source_code, operand.operand_value.row);
 
     if operand.addressing_mode.token_value == addressingmodes::REGISTER.symbol &&
        operand.operand_value.token_type != TokenTypes::Register {
 
-        errors.record_error(operand.operand_value.row, operand.operand_value.column, errormessages::REGISTER_MODE_MISMATCH.error_type, errormessages::REGISTER_MODE_MISMATCH.error_message);
-    
+        errors.record_error_spanned(operand.operand_value.row, operand.operand_value.column, errormessages::REGISTER_MODE_MISMATCH.error_type, errormessages::REGISTER_MODE_MISMATCH.error_message, line, span);
+
     } else if operand.addressing_mode.token_value != addressingmodes::REGISTER.symbol &&
               operand.operand_value.token_type == TokenTypes::Register {
 
-        errors.record_error(operand.operand_value.row, operand.operand_value.column, errormessages::REGISTER_OPERAND_MISMATCH.error_type, errormessages::REGISTER_OPERAND_MISMATCH.error_message);
+        errors.record_error_spanned(operand.operand_value.row, operand.operand_value.column, errormessages::REGISTER_OPERAND_MISMATCH.error_type, errormessages::REGISTER_OPERAND_MISMATCH.error_message, line, span);
+    }
+}
+
+/// Return the literal/label value an operand resolves to, for range checking
+/// `None` for registers (a separate address space, checked via `GPR_ZERO` instead), for labels this
+/// scope hasn't declared (already reported by the `UNDECLARED_LABEL` check) and for variable labels, whose
+/// symbol still holds the variable's initial value here rather than the address the code generator gives it
+/// A branch or procedure label yields its symbol value as it stands before code generation
+fn resolved_operand_value(operand: &Operand, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>) -> Option<isize> {
+
+    match operand.operand_value.token_type {
+
+        TokenTypes::Value => operand.operand_value.token_value.parse().ok(),
+
+        TokenTypes::Label => scope_symbol_table.get(&operand.operand_value.token_value)
+            .or_else(|| global_symbol_table.get(&operand.operand_value.token_value))
+            .filter(|symbol| symbol.symbol_type != SymbolTypes::Variable)
+            .map(|symbol| symbol.symbol_value),
+
+        _ => None
     }
 }
 
 /// Verify the semantic validity of the value of an operand
-fn analyse_operand_value(operand: &Operand, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>, errors: &mut Errors) {
+fn analyse_operand_value(operand: &Operand, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>, operand_bits: usize, source_code: &str, errors: &mut Errors) {
 
+    let span = operand.operand_value.token_value.len();
+    let line = source_line(source_code, operand.operand_value.row);
 
     if operand.operand_value.token_type == TokenTypes::Label &&
        !global_symbol_table.contains_key(&operand.operand_value.token_value) &&
        !scope_symbol_table.contains_key(&operand.operand_value.token_value) {
 
-        errors.record_error(operand.operand_value.row, operand.operand_value.column, errormessages::UNDECLARED_LABEL.error_type, errormessages::UNDECLARED_LABEL.error_message);
+        match nearest_symbol(&operand.operand_value.token_value, scope_symbol_table, global_symbol_table) {
+
+            Some(suggestion) => errors.record_error_noted(operand.operand_value.row, operand.operand_value.column, errormessages::UNDECLARED_LABEL.error_type, errormessages::UNDECLARED_LABEL.error_message, Span { source_line: line, span }, format!("did you mean `{suggestion}`?")),
+            None => errors.record_error_spanned(operand.operand_value.row, operand.operand_value.column, errormessages::UNDECLARED_LABEL.error_type, errormessages::UNDECLARED_LABEL.error_message, line, span)
+        }
 
     } else if operand.operand_value.token_type == TokenTypes::Register &&
               operand.operand_value.token_value == "0" {
 
-        errors.record_error(operand.operand_value.row, operand.operand_value.column, errormessages::GPR_ZERO.error_type, errormessages::GPR_ZERO.error_message);
-    }              
+        errors.record_error_spanned(operand.operand_value.row, operand.operand_value.column, errormessages::GPR_ZERO.error_type, errormessages::GPR_ZERO.error_message, line, span);
+
+    } else if let Some(value) = resolved_operand_value(operand, scope_symbol_table, global_symbol_table) {
+
+        let bound = 2isize.pow(operand_bits as u32 - 1);
+
+        if value < -bound || value > bound - 1 {
+
+            errors.record_error_noted(operand.operand_value.row, operand.operand_value.column, errormessages::OPERAND_OUT_OF_RANGE.error_type, errormessages::OPERAND_OUT_OF_RANGE.error_message, Span { source_line: line, span }, format!("valid range is {}..={}", -bound, bound - 1));
+        }
+    }
 }
 
 /// Verify the semantic validity of an operand as a whole
 #[inline]
-fn analyse_operand(operand: &Operand, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>, errors: &mut Errors) {
+fn analyse_operand(operand: &Operand, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>, operand_bits: usize, source_code: &str, errors: &mut Errors) {
 
-    analyse_addressing_mode(operand, errors);
+    analyse_addressing_mode(operand, source_code, errors);
 
-    analyse_operand_value(operand, scope_symbol_table, global_symbol_table, errors);
+    analyse_operand_value(operand, scope_symbol_table, global_symbol_table, operand_bits, source_code, errors);
 }
 
 /// Get the operand of an instruction
@@ -107,52 +189,55 @@ fn get_operand<'a>(mut index: usize, tokens: &mut Vec<&'a TypedToken>,
 }
 
 /// Verify the semantic validity of an instruction
-fn analyse_instruction<'a>(index: usize, tokens: &mut Vec<&'a TypedToken>, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>, 
-                           default_operands: &(&'a TypedToken, &'a TypedToken, &'a TypedToken, &'a TypedToken), errors: &mut Errors) {
+fn analyse_instruction<'a>(index: usize, tokens: &mut Vec<&'a TypedToken>, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>,
+                           default_operands: &(&'a TypedToken, &'a TypedToken, &'a TypedToken, &'a TypedToken), operand_bits: usize, source_code: &str, errors: &mut Errors) {
 
     let token = tokens[index];
     let instruction = instructions::INSTRUCTION_SET[&token.token_value];
     let number_operands = count_operands(index, tokens);
 
+    let span = token.token_value.len();
+    let line = source_line(source_code, token.row);
+
     if number_operands > instruction.operands {
 
-        errors.record_error(token.row, token.column, errormessages::EXCESS_OPERANDS.error_type, errormessages::EXCESS_OPERANDS.error_message);
+        errors.record_error_spanned(token.row, token.column, errormessages::EXCESS_OPERANDS.error_type, errormessages::EXCESS_OPERANDS.error_message, line, span);
 
     } else if instruction.operands > 1 &&
               tokens[index+1].token_type == TokenTypes::End {
 
-        errors.record_error(token.row, token.column, errormessages::NO_SOURCE_OPERAND.error_type, errormessages::NO_SOURCE_OPERAND.error_message);
+        errors.record_error_spanned(token.row, token.column, errormessages::NO_SOURCE_OPERAND.error_type, errormessages::NO_SOURCE_OPERAND.error_message, line, span);
 
     } else {
 
         if instruction.operands > 0 {
 
             let source_operand = get_operand(index+1, tokens, default_operands);
-            analyse_operand(&source_operand, scope_symbol_table, global_symbol_table, errors);
+            analyse_operand(&source_operand, scope_symbol_table, global_symbol_table, operand_bits, source_code, errors);
 
             // Various Semantic Checks
-            if instruction == &instructions::INP &&
+            if instruction.register_only_operand &&
                source_operand.addressing_mode.token_value != addressingmodes::REGISTER.symbol {
 
-                errors.record_error(token.row, token.column, errormessages::NON_REGISTER_INP_OPERAND.error_type, errormessages::NON_REGISTER_INP_OPERAND.error_message);
+                errors.record_error_spanned(token.row, token.column, errormessages::NON_REGISTER_INP_OPERAND.error_type, format!("{} instruction operand must be a register", instruction.mnemonic), line, span);
             }
 
-            if instructions::NON_IMMEDIATE_MODE_INSTRUCTIONS.contains(instruction.mnemonic) &&
+            if instruction.immediate_disallowed &&
                source_operand.addressing_mode.token_value == addressingmodes::IMMEDIATE.symbol {
 
-                errors.record_error(token.row, token.column, errormessages::IMMEDIATE_MODE.error_type, errormessages::IMMEDIATE_MODE.error_message);
+                errors.record_error_spanned(token.row, token.column, errormessages::IMMEDIATE_MODE.error_type, errormessages::IMMEDIATE_MODE.error_message, line, span);
             }
             //
 
             if instruction.operands > 1 {
 
                 let destination_operand = get_operand(index+3, tokens, default_operands);
-                analyse_operand(&destination_operand, scope_symbol_table, global_symbol_table, errors);
+                analyse_operand(&destination_operand, scope_symbol_table, global_symbol_table, operand_bits, source_code, errors);
 
                 // Various Semantic Checks
                 if destination_operand.addressing_mode.token_value != addressingmodes::REGISTER.symbol {
 
-                    errors.record_error(token.row, token.column, errormessages::NON_REGISTER_DESTINATION_OPERAND.error_type, errormessages::NON_REGISTER_DESTINATION_OPERAND.error_message);
+                    errors.record_error_spanned(token.row, token.column, errormessages::NON_REGISTER_DESTINATION_OPERAND.error_type, errormessages::NON_REGISTER_DESTINATION_OPERAND.error_message, line, span);
                 }
                 //
             }
@@ -162,8 +247,8 @@ fn analyse_instruction<'a>(index: usize, tokens: &mut Vec<&'a TypedToken>, scope
 
 /// Semantically analyse a scope
 #[inline]
-fn semantic_analyse<'a>(tokens: &mut Vec<&'a TypedToken>, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>, 
-                        default_operands: &(&'a TypedToken, &'a TypedToken, &'a TypedToken, &'a TypedToken), errors: &mut Errors) {
+fn semantic_analyse<'a>(tokens: &mut Vec<&'a TypedToken>, scope_symbol_table: &HashMap<&String, Symbol>, global_symbol_table: &HashMap<&String, Symbol>,
+                        default_operands: &(&'a TypedToken, &'a TypedToken, &'a TypedToken, &'a TypedToken), operand_bits: usize, source_code: &str, errors: &mut Errors) {
 
     let mut index = 0;
 
@@ -171,7 +256,7 @@ fn semantic_analyse<'a>(tokens: &mut Vec<&'a TypedToken>, scope_symbol_table: &H
 
         if tokens[index].token_type == TokenTypes::Instruction {
 
-            analyse_instruction(index, tokens, scope_symbol_table, global_symbol_table, default_operands, errors);
+            analyse_instruction(index, tokens, scope_symbol_table, global_symbol_table, default_operands, operand_bits, source_code, errors);
         }
 
         index += 1;
@@ -179,17 +264,24 @@ fn semantic_analyse<'a>(tokens: &mut Vec<&'a TypedToken>, scope_symbol_table: &H
 }
 
 /// Run the SemanticAnalyser
+/// `source_code` is threaded through purely so recorded errors can be rendered as caret-underlined diagnostics
+/// `config_table` is needed to size operand values the same way the code generator does, so out-of-range
+/// immediates/labels are rejected here rather than silently truncated during code generation
 pub fn run<'a>(global_scope: &mut Scope<'a>, procedure_scopes: &mut HashMap<&String, Scope<'a>>,
-               default_operands: &(&'a TypedToken, &'a TypedToken, &'a TypedToken, &'a TypedToken)) {
+               default_operands: &(&'a TypedToken, &'a TypedToken, &'a TypedToken, &'a TypedToken), config_table: &HashMap<String, usize>, source_code: &str) {
 
     let mut errors = Errors::new();
+    let operand_bits = sysdefaults::operand_bits(config_table);
 
-    semantic_analyse(&mut global_scope.tokens, &global_scope.symbol_table, &global_scope.symbol_table, default_operands, &mut errors);
+    semantic_analyse(&mut global_scope.tokens, &global_scope.symbol_table, &global_scope.symbol_table, default_operands, operand_bits, source_code, &mut errors);
 
     for scope in procedure_scopes.values_mut() {
 
-        semantic_analyse(&mut scope.tokens, &scope.symbol_table, &global_scope.symbol_table, default_operands, &mut errors)
+        semantic_analyse(&mut scope.tokens, &scope.symbol_table, &global_scope.symbol_table, default_operands, operand_bits, source_code, &mut errors)
     }
 
-    errors.get_errors(sysdefaults::SEMANTIC_ANALYSER_ERRORS_HEADER);
+    if errors.get_errors(sysdefaults::SEMANTIC_ANALYSER_ERRORS_HEADER) > 0 {
+
+        std::process::exit(sysdefaults::EXIT_CODE);
+    }
 }
@@ -4,6 +4,8 @@ use crate::csm::defaults::sysdefaults;
 use crate::csm::structs::{Memory, MemoryValue};
 use crate::csm::architecture::{registers, addressingmodes};
 use crate::csm::machineoperations::MachineOperations;
+use crate::csm::capabilities::Capabilities;
+use crate::csm::faults::{ControlFlow, Fault};
 use std::{time, thread::sleep};
 
 /// Generate the introduction prompt for the Chadsembler which outlines the system and other useful information
@@ -30,46 +32,46 @@ max_address_value, max_address_value, number_addresses, number_addresses-1, numb
 }
 
 /// Return the address, bits and value an operand points to
-fn resolve_operand(addressing_mode_opcode: usize, operand: &str, memory: &Memory, architecture: isize) -> MemoryValue {
+fn resolve_operand(addressing_mode_opcode: usize, operand: &str, memory: &Memory, architecture: isize) -> Result<MemoryValue, Fault> {
 
     let operand_value = binarystring::read_signed_int(operand).unwrap();
 
-    let mut binary_at_operand = memory.get(operand_value);
+    let mut binary_at_operand = memory.get(operand_value)?;
     let value_at_operand = binarystring::read_signed_int(&binary_at_operand).unwrap();
 
     if addressing_mode_opcode == addressingmodes::INDIRECT.opcode {
 
-        binary_at_operand = memory.get(value_at_operand);
+        binary_at_operand = memory.get(value_at_operand)?;
 
-        MemoryValue {
+        Ok(MemoryValue {
 
             address: value_at_operand,
             value: binarystring::read_signed_int(&binary_at_operand).unwrap(),
             bits: binary_at_operand
-        }
-    
+        })
+
     } else if addressing_mode_opcode == addressingmodes::IMMEDIATE.opcode {
 
-        MemoryValue {
+        Ok(MemoryValue {
 
             address: operand_value,
             bits: binarystring::signed_int(operand_value, architecture),
             value: operand_value
-        }
-    
+        })
+
     } else {
 
-        MemoryValue {
+        Ok(MemoryValue {
 
             address: operand_value,
             bits: binary_at_operand,
             value: value_at_operand
-        }
-    } 
+        })
+    }
 }
 
 /// Split an instruction into its subparts and execute it
-fn handle_instruction(machine_code: &str, memory: &Memory, bits: &(usize, usize, usize, usize)) -> (usize, MemoryValue, MemoryValue) {
+fn handle_instruction(machine_code: &str, memory: &Memory, bits: &(usize, usize, usize, usize)) -> Result<(usize, MemoryValue, MemoryValue), Fault> {
 
     let (mut lower, mut upper) = (0, bits.0);
     let opcode = binarystring::read_unsigned_int(&machine_code[lower..upper]).unwrap() as usize;
@@ -83,24 +85,38 @@ fn handle_instruction(machine_code: &str, memory: &Memory, bits: &(usize, usize,
     (lower, upper) = (upper, upper+bits.2);
     let destination_operand = &machine_code[lower..upper];
 
-    (
+    Ok((
         opcode,
-        resolve_operand(addressing_mode, source_operand, memory, bits.3 as isize),
-        resolve_operand(addressingmodes::REGISTER.opcode, destination_operand, memory, bits.3 as isize)
-    )
+        resolve_operand(addressing_mode, source_operand, memory, bits.3 as isize)?,
+        resolve_operand(addressingmodes::REGISTER.opcode, destination_operand, memory, bits.3 as isize)?
+    ))
 }
 
 /// Run the VirtualMachine
+/// A fault raised mid-instruction is caught here rather than propagated further: if the trap vector register
+/// holds a nonzero handler address, execution is redirected there (with the faulting PC pushed onto the stack
+/// so the handler can `RET` back); otherwise, or when the stack has no room left for it, the fault is reported
+/// and the process exits
 pub fn run(config_table: &HashMap<String, usize>, memory: &mut Memory, bits: &(usize, usize, usize, usize)) {
 
     let number_gprs = config_table[sysdefaults::REGISTERS_CONFIG.0];
     let clock_speed = config_table[sysdefaults::CLOCK_CONFIG.0];
 
+    // A configured value of 0 means no limit, so a buggy program's accidental infinite loop is opt-in rather
+    // than always fatal
+    let max_cycles = match config_table[sysdefaults::CYCLES_CONFIG.0] {
+
+        0 => None,
+        cycles => Some(cycles as u64)
+    };
+
     let program_counter_address = (registers::PROGRAM_COUNTER.offset + number_gprs) as isize * -1;
-    let mut machine_operations = MachineOperations::new(memory, number_gprs);
+    let stack_pointer_address = (registers::STACK_POINTER.offset + number_gprs) as isize * -1;
+    let trap_vector_address = (registers::TRAP_VECTOR.offset + number_gprs) as isize * -1;
+    // The CLI has no way to grant filesystem access yet, so `SYSCALL` can only ever reach `EXIT` here
+    let mut machine_operations = MachineOperations::new(memory, number_gprs, max_cycles, Capabilities::new(Vec::new()));
 
     let mut program_counter = 0;
-    let mut machine_code;
 
     let time = time::Duration::from_millis(clock_speed as u64);
 
@@ -108,15 +124,60 @@ pub fn run(config_table: &HashMap<String, usize>, memory: &mut Memory, bits: &(u
 
     loop {
 
-        machine_code = machine_operations.memory.get(program_counter);
-        machine_operations.memory.insert_binary(program_counter_address, binarystring::unsigned_int(program_counter+1, bits.3 as isize));
+        match step(&mut machine_operations, &mut program_counter, program_counter_address, bits, time) {
+
+            Ok(ControlFlow::Continue) => {},
+            Ok(ControlFlow::Halt) => break,
+            Err(fault) => {
+
+                let handler = binarystring::read_unsigned_int(&machine_operations.memory.get(trap_vector_address)
+                                  .expect("trap vector register address is always valid")).unwrap() as isize;
+
+                let stack_pointer = binarystring::read_signed_int(&machine_operations.memory.get(stack_pointer_address)
+                                        .expect("stack pointer register address is always valid")).unwrap();
+
+                if handler != 0 && stack_pointer > 0 {
 
-        let (opcode, source_operand, destination_operand) = handle_instruction(&machine_code, &machine_operations.memory, bits);
+                    // `program_counter` still holds the address of the faulting instruction: `step` only
+                    // advances it after a successful fetch/decode/execute, so it is the right value to hand
+                    // back to Chadsembly code by pushing it onto the stack, mirroring how `CALL` preserves the PC
+                    if machine_operations.memory.insert_binary(stack_pointer - 1, binarystring::unsigned_int(program_counter, bits.3 as isize)).is_err() {
 
-        sleep(time);
+                        eprintln!("Runtime Error: {fault}");
+                        std::process::exit(sysdefaults::EXIT_CODE);
+                    }
 
-        machine_operations.execute(opcode, source_operand, destination_operand);
+                    machine_operations.memory.insert_value(stack_pointer_address, stack_pointer - 1)
+                                      .expect("stack pointer register address is always valid");
+                    machine_operations.memory.insert_binary(program_counter_address, binarystring::unsigned_int(handler, bits.3 as isize))
+                                      .expect("program counter address is always valid");
 
-        program_counter = binarystring::read_unsigned_int(&machine_operations.memory.get(program_counter_address)).unwrap();
+                    program_counter = handler;
+
+                } else {
+
+                    eprintln!("Runtime Error: {fault}");
+                    std::process::exit(sysdefaults::EXIT_CODE);
+                }
+            }
+        }
     }
 }
+
+/// Fetch, decode and execute a single instruction, advancing the program counter
+fn step<'a>(machine_operations: &mut MachineOperations<'a>, program_counter: &mut isize, program_counter_address: isize,
+           bits: &(usize, usize, usize, usize), time: time::Duration) -> Result<ControlFlow, Fault> {
+
+    let machine_code = machine_operations.memory.get(*program_counter)?;
+    machine_operations.memory.insert_binary(program_counter_address, binarystring::unsigned_int(*program_counter+1, bits.3 as isize))?;
+
+    let (opcode, source_operand, destination_operand) = handle_instruction(&machine_code, machine_operations.memory, bits)?;
+
+    sleep(time);
+
+    let control_flow = machine_operations.execute(opcode, source_operand, destination_operand)?;
+
+    *program_counter = binarystring::read_unsigned_int(&machine_operations.memory.get(program_counter_address)?).unwrap();
+
+    Ok(control_flow)
+}